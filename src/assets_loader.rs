@@ -1,4 +1,33 @@
-use bevy::prelude::*;
+mod manifest;
+
+use bevy::{
+    asset::LoadState, gltf::GltfMesh,
+    platform::collections::HashMap, prelude::*,
+};
+use thiserror::Error;
+
+use crate::{
+    AppState,
+    level::{LEVELS_ORDER, Level, LevelDefinition},
+};
+
+pub use manifest::AssetManifest;
+use manifest::{AssetManifestLoader, ModelEntry};
+
+const MANIFEST_PATH: &str = "assets.manifest.ron";
+
+pub const ROCKET_MODEL: &str = "rocket";
+pub const DEATH_EXPLOSION_SOUND: &str = "death_explosion";
+pub const SUCCESS_SOUND: &str = "success";
+pub const MAIN_ENGINE_THRUST_SOUND: &str = "main_engine_thrust";
+
+// Keys used directly by the game code, levels are validated separately
+const REQUIRED_MODELS: [&str; 1] = [ROCKET_MODEL];
+const REQUIRED_SOUNDS: [&str; 3] = [
+    DEATH_EXPLOSION_SOUND,
+    SUCCESS_SOUND,
+    MAIN_ENGINE_THRUST_SOUND,
+];
 
 #[derive(Debug, Default, Clone)]
 pub struct GameAsset {
@@ -6,17 +35,90 @@ pub struct GameAsset {
     pub collider: Option<Handle<Mesh>>,
 }
 
+#[derive(Debug, Error)]
+pub enum SceneAssetsError {
+    #[error("Scene `{0}` is missing in the asset pack")]
+    MissingScene(String),
+    #[error(
+        "Model `{key}` used by {used_by} is missing in the manifest"
+    )]
+    MissingModel { key: String, used_by: String },
+    #[error(
+        "Collider mesh `{mesh}` of model `{key}` used by {used_by} \
+         is missing in the asset pack"
+    )]
+    MissingCollider {
+        key: String,
+        mesh: String,
+        used_by: String,
+    },
+    #[error("Sound `{0}` is missing in the manifest")]
+    MissingSound(String),
+    #[error(
+        "Definition of level {level:?} failed to load: {reason}"
+    )]
+    LevelNotLoaded { level: Level, reason: String },
+}
+
+#[derive(Debug, Default)]
+pub enum SceneAssetsState {
+    #[default]
+    LoadingManifest,
+    LoadingPack,
+    Ready,
+    Failed(Vec<SceneAssetsError>),
+}
+
 #[derive(Resource, Debug, Default)]
 pub struct SceneAssets {
-    pub floor: GameAsset,
-    pub launch_pad: GameAsset,
-    pub landing_pad: GameAsset,
-    pub rocket: GameAsset,
-    pub obstacle_2: GameAsset,
-    pub obstacle_3: GameAsset,
-    pub death_explosion_sound: Handle<AudioSource>,
-    pub success_sound: Handle<AudioSource>,
-    pub main_engine_thrust_sound: Handle<AudioSource>,
+    pub manifest: Handle<AssetManifest>,
+    pub pack: Handle<Gltf>,
+    pub state: SceneAssetsState,
+    pub models: HashMap<String, GameAsset>,
+    // Only loaded with the audio of the `desktop` feature
+    #[cfg(feature = "desktop")]
+    pub sounds: HashMap<String, Handle<AudioSource>>,
+    // Indexed the same way as `LEVELS_ORDER`
+    pub levels: Vec<Handle<LevelDefinition>>,
+}
+
+impl SceneAssets {
+    pub fn get_game_asset(
+        &self,
+        key: &str,
+    ) -> Option<&GameAsset> {
+        self.models.get(key)
+    }
+
+    #[cfg(feature = "desktop")]
+    pub fn get_sound(
+        &self,
+        key: &str,
+    ) -> Option<&Handle<AudioSource>> {
+        self.sounds.get(key)
+    }
+
+    // Models are resolved from the pack, so they are covered by its
+    // recursive dependencies
+    pub fn untyped_handles(&self) -> Vec<UntypedHandle> {
+        let mut handles = vec![
+            self.manifest.clone().untyped(),
+            self.pack.clone().untyped(),
+        ];
+        #[cfg(feature = "desktop")]
+        handles.extend(
+            self.sounds
+                .values()
+                .map(|sound| sound.clone().untyped()),
+        );
+        handles.extend(
+            self.levels
+                .iter()
+                .map(|level| level.clone().untyped()),
+        );
+
+        handles
+    }
 }
 
 pub struct AssetLoaderPlugin;
@@ -24,7 +126,15 @@ pub struct AssetLoaderPlugin;
 impl Plugin for AssetLoaderPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<SceneAssets>()
-            .add_systems(PreStartup, load_assets);
+            .init_asset::<AssetManifest>()
+            .init_asset_loader::<AssetManifestLoader>()
+            .add_systems(PreStartup, load_assets)
+            .add_systems(
+                Update,
+                (load_manifest_assets, resolve_scene_assets)
+                    .chain()
+                    .run_if(in_state(AppState::Setup)),
+            );
     }
 }
 
@@ -32,104 +142,205 @@ fn load_assets(
     mut scene_assets: ResMut<SceneAssets>,
     asset_server: Res<AssetServer>,
 ) {
-    // TODO: Make proper assets handling and load using one gltf file (check https://bevy.org/examples/assets/multi-asset-sync/ for example)
-    // TODO: Think about better implementation for loading meshes
-
     *scene_assets = SceneAssets {
-        floor: GameAsset {
-            model: asset_server.load(
-                GltfAssetLabel::Scene(0)
-                    .from_asset("floor.gltf"),
-            ),
-            collider: Some(
-                asset_server.load(
-                    GltfAssetLabel::Primitive {
-                        mesh: 0,
-                        primitive: 0,
-                    }
-                    .from_asset("floor.gltf"),
-                ),
-            ),
-        },
-        launch_pad: GameAsset {
-            model: asset_server.load(
-                GltfAssetLabel::Scene(0)
-                    .from_asset("launch_pad.gltf"),
-            ),
-            collider: Some(
-                asset_server.load(
-                    GltfAssetLabel::Primitive {
-                        mesh: 0,
-                        primitive: 0,
-                    }
-                    .from_asset("launch_pad.gltf"),
-                ),
-            ),
-        },
-        landing_pad: GameAsset {
-            model: asset_server.load(
-                GltfAssetLabel::Scene(0)
-                    .from_asset("landing_pad.gltf"),
-            ),
-            collider: Some(
-                asset_server.load(
-                    GltfAssetLabel::Primitive {
-                        mesh: 0,
-                        primitive: 0,
-                    }
-                    .from_asset("landing_pad.gltf"),
-                ),
-            ),
-        },
-        rocket: GameAsset {
-            model: asset_server.load(
-                GltfAssetLabel::Scene(0)
-                    .from_asset("rocket.gltf"),
-            ),
-            collider: Some(
-                asset_server.load(
-                    GltfAssetLabel::Primitive {
-                        mesh: 0,
-                        primitive: 0,
-                    }
-                    .from_asset("rocket_collider.gltf"),
-                ),
-            ),
-        },
-        obstacle_2: GameAsset {
-            model: asset_server.load(
-                GltfAssetLabel::Scene(0)
-                    .from_asset("2_level/obstacle.gltf"),
-            ),
-            collider: Some(
-                asset_server.load(
-                    GltfAssetLabel::Primitive {
-                        mesh: 0,
-                        primitive: 0,
-                    }
-                    .from_asset("2_level/obstacle.gltf"),
-                ),
-            ),
-        },
-        obstacle_3: GameAsset {
-            model: asset_server.load(
-                GltfAssetLabel::Scene(0)
-                    .from_asset("3_level/obstacle.gltf"),
-            ),
-            collider: Some(
-                asset_server.load(
-                    GltfAssetLabel::Primitive {
-                        mesh: 0,
-                        primitive: 0,
-                    }
-                    .from_asset("3_level/obstacle.gltf"),
-                ),
-            ),
-        },
-        death_explosion_sound: asset_server
-            .load("sounds/death_explosion.ogg"),
-        success_sound: asset_server.load("sounds/success.ogg"),
-        main_engine_thrust_sound: asset_server
-            .load("sounds/main_engine_thrust.ogg"),
+        manifest: asset_server.load(MANIFEST_PATH),
+        levels: LEVELS_ORDER
+            .iter()
+            .map(|level| {
+                asset_server.load(level.definition_path())
+            })
+            .collect(),
+        ..default()
+    };
+}
+
+fn load_manifest_assets(
+    mut scene_assets: ResMut<SceneAssets>,
+    asset_server: Res<AssetServer>,
+    manifests: Res<Assets<AssetManifest>>,
+) {
+    if !matches!(
+        scene_assets.state,
+        SceneAssetsState::LoadingManifest
+    ) {
+        return;
+    }
+
+    let Some(manifest) = manifests.get(&scene_assets.manifest)
+    else {
+        return;
     };
+
+    scene_assets.pack = asset_server.load(manifest.pack.clone());
+    #[cfg(feature = "desktop")]
+    {
+        scene_assets.sounds = manifest
+            .sounds
+            .iter()
+            .map(|(key, path)| {
+                (key.clone(), asset_server.load(path.clone()))
+            })
+            .collect();
+    }
+    scene_assets.state = SceneAssetsState::LoadingPack;
+}
+
+fn resolve_game_asset(
+    gltf: &Gltf,
+    gltf_meshes: &Assets<GltfMesh>,
+    model_entry: &ModelEntry,
+    errors: &mut Vec<SceneAssetsError>,
+) -> Option<GameAsset> {
+    let Some(model) =
+        gltf.named_scenes.get(model_entry.scene.as_str())
+    else {
+        errors.push(SceneAssetsError::MissingScene(
+            model_entry.scene.clone(),
+        ));
+        return None;
+    };
+
+    let collider = gltf
+        .named_meshes
+        .get(model_entry.collider_name().as_str())
+        .and_then(|gltf_mesh| gltf_meshes.get(gltf_mesh))
+        .and_then(|gltf_mesh| gltf_mesh.primitives.first())
+        .map(|primitive| primitive.mesh.clone());
+
+    Some(GameAsset {
+        model: model.clone(),
+        collider,
+    })
+}
+
+fn resolve_scene_assets(
+    mut scene_assets: ResMut<SceneAssets>,
+    asset_server: Res<AssetServer>,
+    manifests: Res<Assets<AssetManifest>>,
+    gltfs: Res<Assets<Gltf>>,
+    gltf_meshes: Res<Assets<GltfMesh>>,
+    level_definitions: Res<Assets<LevelDefinition>>,
+) {
+    if !matches!(
+        scene_assets.state,
+        SceneAssetsState::LoadingPack
+    ) || !asset_server
+        .is_loaded_with_dependencies(&scene_assets.pack)
+    {
+        return;
+    }
+
+    // Levels are validated against the manifest, so wait for them too.
+    // One that failed never loads, it is reported instead
+    let mut errors = Vec::new();
+    let mut definitions = Vec::new();
+
+    for (level, handle) in
+        LEVELS_ORDER.iter().zip(scene_assets.levels.iter())
+    {
+        if let Some(definition) = level_definitions.get(handle) {
+            definitions.push((level, definition));
+        } else if let Some(LoadState::Failed(err)) =
+            asset_server.get_load_state(handle)
+        {
+            errors.push(SceneAssetsError::LevelNotLoaded {
+                level: *level,
+                reason: err.to_string(),
+            });
+        } else {
+            return;
+        }
+    }
+
+    let (Some(manifest), Some(gltf)) = (
+        manifests.get(&scene_assets.manifest),
+        gltfs.get(&scene_assets.pack),
+    ) else {
+        return;
+    };
+
+    let models: HashMap<String, GameAsset> = manifest
+        .models
+        .iter()
+        .filter_map(|(key, model_entry)| {
+            resolve_game_asset(
+                gltf,
+                &gltf_meshes,
+                model_entry,
+                &mut errors,
+            )
+            .map(|game_asset| (key.clone(), game_asset))
+        })
+        .collect();
+
+    // The rocket and everything a level places has to collide, only a
+    // model used by neither can go without a collider as scenery
+    let mut used_models: Vec<(&str, String)> = REQUIRED_MODELS
+        .iter()
+        .map(|key| (*key, "the game".to_string()))
+        .collect();
+    for (level, definition) in definitions {
+        used_models.extend(definition.objects().map(
+            |level_object| {
+                (
+                    level_object.asset.as_str(),
+                    format!("level {level:?}"),
+                )
+            },
+        ));
+    }
+
+    for (key, used_by) in used_models.iter() {
+        let Some(model_entry) = manifest.models.get(*key) else {
+            errors.push(SceneAssetsError::MissingModel {
+                key: key.to_string(),
+                used_by: used_by.clone(),
+            });
+            continue;
+        };
+
+        if models.get(*key).is_some_and(|game_asset| {
+            game_asset.collider.is_none()
+        }) {
+            errors.push(SceneAssetsError::MissingCollider {
+                key: key.to_string(),
+                mesh: model_entry.collider_name(),
+                used_by: used_by.clone(),
+            });
+        }
+    }
+    for (key, model_entry) in manifest.models.iter() {
+        let is_scenery =
+            models.get(key).is_some_and(|game_asset| {
+                game_asset.collider.is_none()
+            }) && !used_models
+                .iter()
+                .any(|(used, _)| used == key);
+
+        if is_scenery {
+            warn!(
+                "Collider mesh `{}` is missing in the asset pack, \
+                 `{key}` is only scenery",
+                model_entry.collider_name()
+            );
+        }
+    }
+    for key in REQUIRED_SOUNDS {
+        if !manifest.sounds.contains_key(key) {
+            errors.push(SceneAssetsError::MissingSound(
+                key.to_string(),
+            ));
+        }
+    }
+
+    scene_assets.models = models;
+
+    if errors.is_empty() {
+        scene_assets.state = SceneAssetsState::Ready;
+    } else {
+        for err in errors.iter() {
+            error!("{err}");
+        }
+        scene_assets.state = SceneAssetsState::Failed(errors);
+    }
 }
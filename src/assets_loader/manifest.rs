@@ -0,0 +1,71 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    platform::collections::HashMap,
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+const COLLIDER_MESH_SUFFIX: &str = "_collider";
+
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct AssetManifest {
+    // Path of the glTF asset pack with all models
+    pub pack: String,
+    #[serde(default)]
+    pub models: HashMap<String, ModelEntry>,
+    // Key to audio file path
+    #[serde(default)]
+    pub sounds: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModelEntry {
+    // Name of the scene in the asset pack
+    pub scene: String,
+    // Name of the collider mesh in the asset pack, defaults to
+    // `<scene>_collider`
+    #[serde(default)]
+    pub collider: Option<String>,
+}
+
+impl ModelEntry {
+    pub fn collider_name(&self) -> String {
+        self.collider.clone().unwrap_or_else(|| {
+            format!("{}{COLLIDER_MESH_SUFFIX}", self.scene)
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct AssetManifestLoader;
+
+#[derive(Debug, Error)]
+pub enum AssetManifestLoaderError {
+    #[error("Could not read asset manifest: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse asset manifest: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for AssetManifestLoader {
+    type Asset = AssetManifest;
+    type Settings = ();
+    type Error = AssetManifestLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        Ok(ron::de::from_bytes::<AssetManifest>(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["manifest.ron"]
+    }
+}
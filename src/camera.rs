@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    assets_loader::SceneAssets,
+    collision::ColliderAabb,
+    level::{CurrentLevel, Finish, LevelDefinition},
+    player::Player,
+    save::{NewLeaderboardEntry, Settings},
+};
+
+const FIXED_POSITION: Vec3 = Vec3::new(0.0, 4.5, 9.0);
+const FIXED_LOOK_AT: Vec3 = Vec3::new(0.0, 2.0, 0.0);
+// Extra space around the player and the landing pad when framing both
+const FRAMING_MARGIN: f32 = 1.4;
+const CINEMATIC_SWAY_SPEED: f32 = 0.3;
+const CINEMATIC_SWAY_DEGREES: f32 = 25.0;
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
+pub enum CameraMode {
+    Fixed,
+    #[default]
+    Follow,
+    Cinematic,
+}
+
+impl CameraMode {
+    fn next(self) -> Self {
+        match self {
+            CameraMode::Fixed => CameraMode::Follow,
+            CameraMode::Follow => CameraMode::Cinematic,
+            CameraMode::Cinematic => CameraMode::Fixed,
+        }
+    }
+}
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct CameraRig {
+    pub mode: CameraMode,
+    // Offset from the focus point in follow and cinematic modes
+    pub offset: Vec3,
+    // Stops zooming out to keep the landing pad in frame past this
+    pub max_distance: f32,
+    pub smoothness: f32,
+}
+
+impl Default for CameraRig {
+    fn default() -> Self {
+        Self {
+            mode: CameraMode::default(),
+            offset: Vec3::new(0.0, 2.5, 9.0),
+            max_distance: 20.0,
+            smoothness: 4.0,
+        }
+    }
+}
+
+// Limits for the point the camera looks at, so it never shows what is
+// outside of the level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraBounds {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl CameraBounds {
+    fn clamp(&self, point: Vec3) -> Vec3 {
+        point
+            .truncate()
+            .clamp(self.min, self.max)
+            .extend(point.z)
+    }
+}
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<CameraRig>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    switch_mode
+                        // C is also typed into the leaderboard name
+                        .run_if(not(resource_exists::<
+                            NewLeaderboardEntry,
+                        >)),
+                    update_rig,
+                )
+                    .chain()
+                    // The editor moves the camera on its own
+                    .run_if(not(in_state(AppState::Editor))),
+            );
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Main camera"),
+        Camera3d::default(),
+        Projection::Perspective(PerspectiveProjection {
+            fov: 65.0f32.to_radians(),
+            ..default()
+        }),
+        Transform::from_translation(FIXED_POSITION)
+            .looking_at(FIXED_LOOK_AT, Vec3::Y),
+        CameraRig::default(),
+    ));
+}
+
+// Goes through the settings, so the picked mode is remembered
+fn switch_mode(
+    keys: Res<ButtonInput<KeyCode>>,
+    rig: Single<&CameraRig>,
+    mut settings: ResMut<Settings>,
+) {
+    if keys.just_pressed(KeyCode::KeyC) {
+        settings.camera_mode = rig.mode.next();
+    }
+}
+
+fn update_rig(
+    mut camera: Single<(
+        &mut Transform,
+        &CameraRig,
+        &Projection,
+    )>,
+    player: Option<Single<&GlobalTransform, With<Player>>>,
+    finish_query: Query<&ColliderAabb, With<Finish>>,
+    current_level: Res<CurrentLevel>,
+    scene_assets: Res<SceneAssets>,
+    level_definitions: Res<Assets<LevelDefinition>>,
+    time: Res<Time>,
+) {
+    let (transform, rig, projection) = &mut *camera;
+    let bounds = current_level
+        .definition(&scene_assets, &level_definitions)
+        .and_then(|definition| {
+            definition.camera_bounds.as_ref()
+        });
+    let clamp_to_bounds = |point: Vec3| match bounds {
+        Some(bounds) => bounds.clamp(point),
+        None => point,
+    };
+
+    let (target_position, target_look_at) =
+        match (rig.mode, player) {
+            (CameraMode::Fixed, _) | (_, None) => {
+                (FIXED_POSITION, FIXED_LOOK_AT)
+            }
+            (CameraMode::Follow, Some(player)) => {
+                let player_position = player.translation();
+                let fov = match projection {
+                    Projection::Perspective(perspective) => {
+                        perspective.fov
+                    }
+                    _ => 65.0f32.to_radians(),
+                };
+                let base_distance = rig.offset.length();
+
+                // Frame the landing pad too, unless it's too far away
+                let (focus, distance) = finish_query
+                    .iter()
+                    .next()
+                    .map(|finish_aabb| {
+                        let finish_position =
+                            finish_aabb.center();
+                        let span = player_position
+                            .distance(finish_position)
+                            * FRAMING_MARGIN;
+                        let distance =
+                            span / (2.0 * (fov / 2.0).tan());
+
+                        (
+                            player_position
+                                .lerp(finish_position, 0.5),
+                            distance.max(base_distance),
+                        )
+                    })
+                    .filter(|(_, distance)| {
+                        *distance <= rig.max_distance
+                    })
+                    .unwrap_or((player_position, base_distance));
+
+                let focus = clamp_to_bounds(focus);
+
+                (
+                    focus
+                        + rig.offset.normalize_or_zero()
+                            * distance,
+                    focus,
+                )
+            }
+            (CameraMode::Cinematic, Some(player)) => {
+                let focus =
+                    clamp_to_bounds(player.translation());
+                let sway = (time.elapsed_secs()
+                    * CINEMATIC_SWAY_SPEED)
+                    .sin()
+                    * CINEMATIC_SWAY_DEGREES.to_radians();
+
+                (
+                    focus
+                        + Quat::from_rotation_y(sway)
+                            * rig.offset,
+                    focus,
+                )
+            }
+        };
+
+    transform.translation.smooth_nudge(
+        &target_position,
+        rig.smoothness,
+        time.delta_secs(),
+    );
+
+    let target_rotation =
+        transform.looking_at(target_look_at, Vec3::Y).rotation;
+    transform.rotation.smooth_nudge(
+        &target_rotation,
+        rig.smoothness,
+        time.delta_secs(),
+    );
+}
@@ -2,12 +2,10 @@ use avian3d::prelude::*;
 use bevy::prelude::*;
 
 pub use avian3d::prelude::{
-    AngularVelocity, LinearVelocity, RigidBody,
+    AngularVelocity, ColliderAabb, LinearVelocity, RigidBody,
 };
 
-use crate::IN_DEVELOPMENT;
-
-pub const IS_COLLIDER_WIREFRAME_ENABLED: bool = false;
+use crate::{AppState, player::RocketTuning};
 
 pub struct CollisionPlugin;
 
@@ -15,11 +13,28 @@ impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((PhysicsPlugins::default(),))
             .add_event::<CollisionEvent>()
-            .add_systems(Update, on_collide);
-
-        if IN_DEVELOPMENT && IS_COLLIDER_WIREFRAME_ENABLED {
-            app.add_plugins(PhysicsDebugPlugin::default());
-        }
+            .add_event::<CollisionEndedEvent>()
+            // Right after each physics step, the outcomes of a try are
+            // checked on the following fixed tick
+            .add_systems(
+                FixedPostUpdate,
+                (on_collide, on_collision_end)
+                    .after(PhysicsSet::Sync),
+            )
+            .add_systems(
+                OnEnter(AppState::Paused),
+                pause_physics,
+            )
+            .add_systems(
+                OnEnter(AppState::Editor),
+                pause_physics,
+            )
+            // Not on leaving `Paused`, so the world stays frozen behind
+            // the main menu opened from the pause menu
+            .add_systems(
+                OnEnter(AppState::InGame),
+                resume_physics,
+            );
     }
 }
 
@@ -30,16 +45,50 @@ pub fn create_collider(
     (
         collider_type,
         Mesh3d(collider.clone()),
-        Mass(100.0),
         ColliderDensity::default(),
+        ColliderConstructor::TrimeshFromMesh,
+        CollisionEventsEnabled,
+    )
+}
+
+pub fn create_physics_material(
+    tuning: &RocketTuning,
+) -> impl Bundle {
+    (
+        Mass(tuning.mass),
+        Restitution::new(tuning.restitution),
+        Friction::new(tuning.friction),
+        CollisionMargin(tuning.collision_margin),
+    )
+}
+
+// Level geometry feels the same whatever the rocket is tuned to
+pub fn create_static_material() -> impl Bundle {
+    (
         Restitution::new(0.01),
         Friction::new(0.8),
         CollisionMargin(0.05),
-        ColliderConstructor::TrimeshFromMesh,
+    )
+}
+
+// Detects overlaps without physically blocking other bodies
+pub fn create_sensor_collider(radius: f32) -> impl Bundle {
+    (
+        RigidBody::Static,
+        Collider::sphere(radius),
+        Sensor,
         CollisionEventsEnabled,
     )
 }
 
+fn pause_physics(mut time: ResMut<Time<Physics>>) {
+    time.pause();
+}
+
+fn resume_physics(mut time: ResMut<Time<Physics>>) {
+    time.unpause();
+}
+
 #[derive(Event)]
 pub struct CollisionEvent(pub Entity, pub Entity);
 
@@ -51,3 +100,16 @@ fn on_collide(
         ew_collision.write(CollisionEvent(ev.0, ev.1));
     }
 }
+
+#[derive(Event)]
+pub struct CollisionEndedEvent(pub Entity, pub Entity);
+
+fn on_collision_end(
+    mut ended: EventReader<CollisionEnded>,
+    mut ew_collision_ended: EventWriter<CollisionEndedEvent>,
+) {
+    for ev in ended.read() {
+        ew_collision_ended
+            .write(CollisionEndedEvent(ev.0, ev.1));
+    }
+}
@@ -0,0 +1,308 @@
+use bevy::{platform::collections::HashMap, prelude::*};
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::save::Settings;
+
+#[derive(
+    Actionlike,
+    PartialEq,
+    Eq,
+    Hash,
+    Clone,
+    Copy,
+    Debug,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
+pub enum Action {
+    Boost,
+    RotateLeft,
+    RotateRight,
+    Confirm,
+    Pause,
+    Restart,
+    #[actionlike(Axis)]
+    Thrust,
+    #[actionlike(Axis)]
+    Rotate,
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::Boost,
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::Confirm,
+        Action::Pause,
+        Action::Restart,
+        Action::Thrust,
+        Action::Rotate,
+    ];
+
+    pub fn is_axis(&self) -> bool {
+        self.input_control_kind() == InputControlKind::Axis
+    }
+}
+
+// Axes offered when rebinding an analog action
+const REBINDABLE_AXES: [GamepadAxis; 6] = [
+    GamepadAxis::LeftStickX,
+    GamepadAxis::LeftStickY,
+    GamepadAxis::LeftZ,
+    GamepadAxis::RightStickX,
+    GamepadAxis::RightStickY,
+    GamepadAxis::RightZ,
+];
+const REBIND_AXIS_THRESHOLD: f32 = 0.5;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum GamepadInput {
+    Button(GamepadButton),
+    Axis(GamepadAxis),
+}
+
+impl GamepadInput {
+    fn name(&self) -> String {
+        match self {
+            GamepadInput::Button(button) => {
+                format!("{button:?}")
+            }
+            GamepadInput::Axis(axis) => format!("{axis:?}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionBindings {
+    pub keys: Vec<KeyCode>,
+    pub gamepad: Vec<GamepadInput>,
+}
+
+fn default_bindings(action: Action) -> ActionBindings {
+    let (keys, gamepad) = match action {
+        Action::Boost => (
+            vec![KeyCode::Space, KeyCode::KeyW],
+            vec![GamepadInput::Button(
+                GamepadButton::RightTrigger2,
+            )],
+        ),
+        Action::RotateLeft => (
+            vec![KeyCode::KeyA, KeyCode::ArrowLeft],
+            // The stick drives the analog `Rotate` instead
+            vec![GamepadInput::Button(GamepadButton::DPadLeft)],
+        ),
+        Action::RotateRight => (
+            vec![KeyCode::KeyD, KeyCode::ArrowRight],
+            vec![GamepadInput::Button(GamepadButton::DPadRight)],
+        ),
+        Action::Confirm => (
+            vec![KeyCode::Space, KeyCode::Enter],
+            vec![GamepadInput::Button(GamepadButton::South)],
+        ),
+        Action::Pause => (
+            vec![KeyCode::Escape],
+            vec![GamepadInput::Button(GamepadButton::Start)],
+        ),
+        Action::Restart => (
+            vec![KeyCode::KeyR],
+            vec![GamepadInput::Button(GamepadButton::North)],
+        ),
+        // The trigger of `Boost` reports how far it is pulled already,
+        // this is for controllers that only expose it as an axis
+        Action::Thrust => (vec![], vec![]),
+        Action::Rotate => (
+            vec![],
+            vec![GamepadInput::Axis(GamepadAxis::LeftStickX)],
+        ),
+    };
+
+    ActionBindings { keys, gamepad }
+}
+
+// Only the rebound actions are stored, the rest use the defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Bindings(pub HashMap<Action, ActionBindings>);
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> ActionBindings {
+        self.0
+            .get(&action)
+            .cloned()
+            .unwrap_or_else(|| default_bindings(action))
+    }
+
+    pub fn describe(&self, action: Action) -> String {
+        let bindings = self.get(action);
+
+        bindings
+            .keys
+            .iter()
+            .map(|key| format!("{key:?}"))
+            .chain(
+                bindings.gamepad.iter().map(GamepadInput::name),
+            )
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn key_name(&self, action: Action) -> String {
+        self.get(action)
+            .keys
+            .first()
+            .map(|key| format!("{key:?}"))
+            .unwrap_or_else(|| "?".to_string())
+    }
+
+    pub fn input_map(&self) -> InputMap<Action> {
+        let mut input_map = InputMap::default();
+
+        for action in Action::ALL {
+            let bindings = self.get(action);
+
+            for key in bindings.keys {
+                input_map.insert(action, key);
+            }
+            for input in bindings.gamepad {
+                match input {
+                    GamepadInput::Button(button) => {
+                        input_map.insert(action, button);
+                    }
+                    GamepadInput::Axis(axis) => {
+                        input_map.insert_axis(
+                            action,
+                            GamepadControlAxis::new(axis),
+                        );
+                    }
+                }
+            }
+        }
+
+        input_map
+    }
+}
+
+// How raw trigger and stick values turn into thrust and rotation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalogSettings {
+    pub deadzone: f32,
+    // 1.0 is linear, higher values give finer control near the center
+    pub response_exponent: f32,
+}
+
+impl Default for AnalogSettings {
+    fn default() -> Self {
+        Self {
+            deadzone: 0.15,
+            response_exponent: 1.5,
+        }
+    }
+}
+
+impl AnalogSettings {
+    pub fn apply(&self, value: f32) -> f32 {
+        let magnitude = value.abs().min(1.0);
+        if magnitude <= self.deadzone {
+            return 0.0;
+        }
+
+        let rescaled =
+            (magnitude - self.deadzone) / (1.0 - self.deadzone);
+
+        rescaled.powf(self.response_exponent).copysign(value)
+    }
+}
+
+// The action waiting for a new key, gamepad button or axis on the
+// controls screen
+#[derive(Resource, Debug, Default)]
+pub struct Rebinding(pub Option<Action>);
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(InputManagerPlugin::<Action>::default())
+            // Global actions for menus and results, the rocket reads its
+            // own `ActionState` into `RocketInput`
+            .init_resource::<ActionState<Action>>()
+            .insert_resource(Bindings::default().input_map())
+            .init_resource::<Rebinding>()
+            .add_systems(
+                Update,
+                (
+                    apply_bindings
+                        .run_if(resource_changed::<Settings>),
+                    capture_rebinding,
+                ),
+            );
+    }
+}
+
+fn apply_bindings(
+    mut commands: Commands,
+    mut input_map_query: Query<&mut InputMap<Action>>,
+    settings: Res<Settings>,
+) {
+    let input_map = settings.bindings.input_map();
+
+    for mut entity_input_map in input_map_query.iter_mut() {
+        *entity_input_map = input_map.clone();
+    }
+    commands.insert_resource(input_map);
+}
+
+fn capture_rebinding(
+    mut rebinding: ResMut<Rebinding>,
+    mut settings: ResMut<Settings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Escape) {
+        rebinding.0 = None;
+        return;
+    }
+
+    let mut bindings = settings.bindings.get(action);
+
+    if action.is_axis() {
+        let Some(axis) = gamepads.iter().find_map(|gamepad| {
+            REBINDABLE_AXES.into_iter().find(|axis| {
+                gamepad.get(*axis).is_some_and(|value| {
+                    value.abs() > REBIND_AXIS_THRESHOLD
+                })
+            })
+        }) else {
+            return;
+        };
+
+        bindings.gamepad = vec![GamepadInput::Axis(axis)];
+        settings.bindings.0.insert(action, bindings);
+        rebinding.0 = None;
+        return;
+    }
+
+    let pressed_button = gamepads.iter().find_map(|gamepad| {
+        gamepad.get_just_pressed().next().copied()
+    });
+
+    if let Some(key) = keys.get_just_pressed().next() {
+        bindings.keys = vec![*key];
+    } else if let Some(button) = pressed_button {
+        bindings.gamepad = vec![GamepadInput::Button(button)];
+    } else {
+        return;
+    }
+
+    settings.bindings.0.insert(action, bindings);
+    rebinding.0 = None;
+}
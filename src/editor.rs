@@ -0,0 +1,656 @@
+mod export;
+mod free_camera;
+mod handles;
+
+use bevy::prelude::*;
+
+use crate::{
+    AppState,
+    assets_loader::SceneAssets,
+    level::{
+        ChangeEvent, CurrentLevel, LEVELS_ORDER,
+        LevelDefinition, LevelMarker, TweenMove,
+    },
+    player::Player,
+    save::NewLeaderboardEntry,
+};
+
+use export::save_definition;
+pub use export::{EditorError, build_definition};
+
+const ENTER_KEY: KeyCode = KeyCode::F2;
+const TEST_FLY_KEY: KeyCode = KeyCode::F5;
+const MOVE_STEP: f32 = 0.5;
+const ROTATE_STEP_DEGREES: f32 = 15.0;
+const SCALE_STEP: f32 = 0.1;
+// Holding shift divides every step by this
+const FINE_STEP_DIVISOR: f32 = 10.0;
+const DEFAULT_TWEEN_OFFSET: Vec3 = Vec3::new(0.0, 2.0, 0.0);
+const DEFAULT_TWEEN_DURATION: usize = 3;
+
+const HELP: &str = "\
+Tab select  N new  Delete remove  [ ] asset
+1 move  2 rotate  3 scale  4 tween target
+Arrows X/Y  PageUp/PageDown Z  Shift fine steps
+T tag  M toggle tween  +/- tween duration
+Click select  Drag handles move/rotate/scale
+Right drag look  WASD/Q/E fly  Scroll zoom
+F5 test-fly  Ctrl+S save  Escape main menu";
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            enter
+                .run_if(
+                    in_state(AppState::MainMenu)
+                        .or(in_state(AppState::InGame))
+                        .or(in_state(AppState::Paused))
+                        .or(in_state(AppState::Failed))
+                        .or(in_state(AppState::Succeed)),
+                )
+                .run_if(not(resource_exists::<
+                    NewLeaderboardEntry,
+                >)),
+        )
+        .add_systems(OnEnter(AppState::Editor), setup)
+        .add_systems(
+            Update,
+            (
+                (
+                    select_object,
+                    handles::drag,
+                    pick_tool_and_asset,
+                    add_or_remove_object,
+                    edit_selected,
+                )
+                    .chain(),
+                free_camera::fly,
+                test_fly,
+                save,
+                leave,
+                draw_gizmos,
+                update_status_text
+                    .run_if(resource_changed::<EditorSession>),
+            )
+                .run_if(in_state(AppState::Editor))
+                // Missing if the level could not be edited
+                .run_if(resource_exists::<EditorSession>),
+        )
+        .add_systems(OnExit(AppState::Editor), despawn);
+    }
+}
+
+// Which part of the definition a placed object ends up in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorTag {
+    Floor,
+    LaunchPad,
+    // The landing pad, reaching it finishes the level
+    Finish,
+    Obstacle,
+}
+
+impl EditorTag {
+    fn next(self) -> Self {
+        match self {
+            EditorTag::Floor => EditorTag::LaunchPad,
+            EditorTag::LaunchPad => EditorTag::Finish,
+            EditorTag::Finish => EditorTag::Obstacle,
+            EditorTag::Obstacle => EditorTag::Floor,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            EditorTag::Floor => Color::srgb(0.6, 0.6, 0.6),
+            EditorTag::LaunchPad => Color::srgb(0.2, 0.5, 1.0),
+            EditorTag::Finish => Color::srgb(0.2, 0.9, 0.3),
+            EditorTag::Obstacle => Color::srgb(1.0, 0.3, 0.2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum EditorTool {
+    #[default]
+    Move,
+    Rotate,
+    Scale,
+    TweenTarget,
+}
+
+// Placed without colliders, the level is only built from these on
+// test-fly or save
+#[derive(Component, Debug, Clone)]
+pub struct EditorObject {
+    pub asset: String,
+    pub tag: EditorTag,
+    pub tween_move: Option<TweenMove>,
+}
+
+#[derive(Resource, Debug)]
+struct EditorSession {
+    // Index of the edited level in `LEVELS_ORDER`
+    level_index: usize,
+    // Keeps the fields the editor does not change, like fuel and
+    // tuning, for the exported definition
+    template: LevelDefinition,
+    // Keys of the assets that can be placed, the ones without a
+    // collider end up as scenery
+    assets: Vec<String>,
+    asset_index: usize,
+    tool: EditorTool,
+    selected: Option<Entity>,
+    drag: Option<handles::Drag>,
+    is_modified: bool,
+    message: String,
+}
+
+#[derive(Component)]
+struct EditorText;
+
+fn enter(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keys.just_pressed(ENTER_KEY) {
+        next_state.set(AppState::Editor);
+    }
+}
+
+// Swaps the playable level for editable copies of its objects
+fn setup(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<AppState>>,
+    current_level: Res<CurrentLevel>,
+    scene_assets: Res<SceneAssets>,
+    level_definitions: Res<Assets<LevelDefinition>>,
+    level_query: Query<Entity, With<LevelMarker>>,
+    player_query: Query<Entity, With<Player>>,
+) {
+    let Some(definition) = current_level
+        .definition(&scene_assets, &level_definitions)
+    else {
+        error!(
+            "Level {:?} is not loaded, can't edit it",
+            current_level.level()
+        );
+        next_state.set(AppState::MainMenu);
+        return;
+    };
+
+    for entity in level_query.iter().chain(player_query.iter()) {
+        commands.entity(entity).despawn();
+    }
+
+    for (object, transform) in editor_objects(definition) {
+        spawn_object(
+            &mut commands,
+            &scene_assets,
+            object,
+            transform,
+        );
+    }
+
+    let mut assets: Vec<String> =
+        scene_assets.models.keys().cloned().collect();
+    assets.sort();
+
+    commands.insert_resource(EditorSession {
+        level_index: current_level.index,
+        template: definition.clone(),
+        assets,
+        asset_index: 0,
+        tool: EditorTool::default(),
+        selected: None,
+        drag: None,
+        is_modified: false,
+        message: String::new(),
+    });
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        },
+        Text::default(),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        Name::new("Editor text"),
+        EditorText,
+    ));
+}
+
+// The objects of a definition as the editor places them, turned back
+// into a definition by `build_definition`
+pub fn editor_objects(
+    definition: &LevelDefinition,
+) -> impl Iterator<Item = (EditorObject, Transform)> {
+    [
+        (&definition.floor, EditorTag::Floor),
+        (&definition.launch_pad, EditorTag::LaunchPad),
+        (&definition.landing_pad, EditorTag::Finish),
+    ]
+    .into_iter()
+    .chain(
+        definition
+            .obstacles
+            .iter()
+            .map(|obstacle| (obstacle, EditorTag::Obstacle)),
+    )
+    .map(|(level_object, tag)| {
+        (
+            EditorObject {
+                asset: level_object.asset.clone(),
+                tag,
+                tween_move: level_object.tween_move.clone(),
+            },
+            Transform::from(&level_object.transform),
+        )
+    })
+}
+
+fn spawn_object(
+    commands: &mut Commands,
+    scene_assets: &SceneAssets,
+    object: EditorObject,
+    transform: Transform,
+) -> Option<Entity> {
+    let Some(game_asset) =
+        scene_assets.get_game_asset(&object.asset)
+    else {
+        warn!("Unknown level asset: {}", object.asset);
+        return None;
+    };
+
+    let entity = commands
+        .spawn((
+            SceneRoot(game_asset.model.clone()),
+            transform,
+            Name::new(format!("Editor {}", object.asset)),
+            object,
+        ))
+        .id();
+
+    Some(entity)
+}
+
+fn select_object(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut session: ResMut<EditorSession>,
+    object_query: Query<Entity, With<EditorObject>>,
+) {
+    if !keys.just_pressed(KeyCode::Tab) {
+        return;
+    }
+
+    let mut entities: Vec<Entity> =
+        object_query.iter().collect();
+    entities.sort();
+    if entities.is_empty() {
+        return;
+    }
+
+    let current = session.selected.and_then(|selected| {
+        entities.iter().position(|entity| *entity == selected)
+    });
+    let index = match (current, is_shift_pressed(&keys)) {
+        (None, _) => 0,
+        (Some(index), false) => (index + 1) % entities.len(),
+        (Some(index), true) => {
+            (index + entities.len() - 1) % entities.len()
+        }
+    };
+
+    session.selected = Some(entities[index]);
+}
+
+fn pick_tool_and_asset(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut session: ResMut<EditorSession>,
+) {
+    let tools = [
+        (KeyCode::Digit1, EditorTool::Move),
+        (KeyCode::Digit2, EditorTool::Rotate),
+        (KeyCode::Digit3, EditorTool::Scale),
+        (KeyCode::Digit4, EditorTool::TweenTarget),
+    ];
+    for (key, tool) in tools {
+        if keys.just_pressed(key) {
+            session.tool = tool;
+        }
+    }
+
+    let count = session.assets.len().max(1);
+    if keys.just_pressed(KeyCode::BracketRight) {
+        session.asset_index = (session.asset_index + 1) % count;
+    }
+    if keys.just_pressed(KeyCode::BracketLeft) {
+        session.asset_index =
+            (session.asset_index + count - 1) % count;
+    }
+}
+
+// New objects start as obstacles at the origin, like in the level files
+fn add_or_remove_object(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut session: ResMut<EditorSession>,
+    scene_assets: Res<SceneAssets>,
+) {
+    if keys.just_pressed(KeyCode::KeyN)
+        && let Some(asset) =
+            session.assets.get(session.asset_index).cloned()
+    {
+        let object = EditorObject {
+            asset,
+            tag: EditorTag::Obstacle,
+            tween_move: None,
+        };
+        if let Some(entity) = spawn_object(
+            &mut commands,
+            &scene_assets,
+            object,
+            Transform::default(),
+        ) {
+            session.selected = Some(entity);
+            session.is_modified = true;
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Delete)
+        && let Some(selected) = session.selected.take()
+    {
+        commands.entity(selected).despawn();
+        session.is_modified = true;
+    }
+}
+
+fn edit_selected(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut session: ResMut<EditorSession>,
+    mut object_query: Query<(&mut Transform, &mut EditorObject)>,
+) {
+    let Some((mut transform, mut object)) =
+        session.selected.and_then(|selected| {
+            object_query.get_mut(selected).ok()
+        })
+    else {
+        return;
+    };
+
+    let mut is_modified = false;
+
+    let axes = [
+        (KeyCode::ArrowRight, Vec3::X),
+        (KeyCode::ArrowLeft, Vec3::NEG_X),
+        (KeyCode::ArrowUp, Vec3::Y),
+        (KeyCode::ArrowDown, Vec3::NEG_Y),
+        (KeyCode::PageUp, Vec3::Z),
+        (KeyCode::PageDown, Vec3::NEG_Z),
+    ];
+    let direction: Vec3 = axes
+        .into_iter()
+        .filter(|(key, _)| keys.just_pressed(*key))
+        .map(|(_, axis)| axis)
+        .sum();
+
+    if direction != Vec3::ZERO {
+        let divisor = if is_shift_pressed(&keys) {
+            FINE_STEP_DIVISOR
+        } else {
+            1.0
+        };
+
+        match session.tool {
+            EditorTool::Move => {
+                transform.translation +=
+                    direction * MOVE_STEP / divisor;
+            }
+            EditorTool::Rotate => {
+                let angles = direction
+                    * (ROTATE_STEP_DEGREES / divisor)
+                        .to_radians();
+                transform.rotation = Quat::from_euler(
+                    EulerRot::XYZ,
+                    angles.x,
+                    angles.y,
+                    angles.z,
+                ) * transform.rotation;
+            }
+            EditorTool::Scale => {
+                transform.scale = (transform.scale
+                    + direction * SCALE_STEP / divisor)
+                    .max(Vec3::splat(SCALE_STEP / divisor));
+            }
+            EditorTool::TweenTarget => {
+                if let Some(tween_move) = &mut object.tween_move
+                {
+                    tween_move.target +=
+                        direction * MOVE_STEP / divisor;
+                }
+            }
+        }
+        is_modified = true;
+    }
+
+    if keys.just_pressed(KeyCode::KeyT) {
+        object.tag = object.tag.next();
+        is_modified = true;
+    }
+
+    if keys.just_pressed(KeyCode::KeyM) {
+        object.tween_move = match object.tween_move {
+            Some(_) => None,
+            None => Some(TweenMove {
+                target: transform.translation
+                    + DEFAULT_TWEEN_OFFSET,
+                duration: DEFAULT_TWEEN_DURATION,
+            }),
+        };
+        is_modified = true;
+    }
+
+    if let Some(tween_move) = &mut object.tween_move {
+        if keys.just_pressed(KeyCode::Equal) {
+            tween_move.duration += 1;
+            is_modified = true;
+        }
+        if keys.just_pressed(KeyCode::Minus) {
+            tween_move.duration =
+                tween_move.duration.saturating_sub(1).max(1);
+            is_modified = true;
+        }
+    }
+
+    if is_modified {
+        session.is_modified = true;
+    }
+}
+
+// Plays the edited level without saving it. The definition is added as
+// a new asset, the loaded one stays as it is on disk
+fn test_fly(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut session: ResMut<EditorSession>,
+    object_query: Query<(Entity, &EditorObject, &Transform)>,
+    mut level_definitions: ResMut<Assets<LevelDefinition>>,
+    mut ew_change: EventWriter<ChangeEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if !keys.just_pressed(TEST_FLY_KEY) {
+        return;
+    }
+
+    match build_definition(
+        &session.template,
+        object_query.iter(),
+    ) {
+        Ok(definition) => {
+            ew_change.write(ChangeEvent::TestFly(
+                level_definitions.add(definition),
+            ));
+            next_state.set(AppState::InGame);
+        }
+        Err(err) => session.message = err.to_string(),
+    }
+}
+
+fn save(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut session: ResMut<EditorSession>,
+    object_query: Query<(Entity, &EditorObject, &Transform)>,
+) {
+    if !(is_ctrl_pressed(&keys)
+        && keys.just_pressed(KeyCode::KeyS))
+    {
+        return;
+    }
+
+    let level = LEVELS_ORDER[session.level_index];
+    let result =
+        build_definition(&session.template, object_query.iter())
+            .and_then(|definition| {
+                save_definition(level, &definition)
+            });
+
+    session.message = match result {
+        Ok(path) => {
+            session.is_modified = false;
+            format!("Saved to {}", path.display())
+        }
+        Err(err) => {
+            error!("{err}");
+            err.to_string()
+        }
+    };
+}
+
+// Unsaved edits are dropped, picking the level again also ends the
+// test flight so it comes back as it is on disk
+fn leave(
+    keys: Res<ButtonInput<KeyCode>>,
+    session: Res<EditorSession>,
+    mut ew_change: EventWriter<ChangeEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        ew_change.write(ChangeEvent::Set(
+            LEVELS_ORDER[session.level_index],
+        ));
+        next_state.set(AppState::MainMenu);
+    }
+}
+
+fn draw_gizmos(
+    mut gizmos: Gizmos,
+    session: Res<EditorSession>,
+    object_query: Query<(Entity, &EditorObject, &Transform)>,
+) {
+    for (entity, object, transform) in object_query.iter() {
+        let color = object.tag.color();
+
+        gizmos.sphere(transform.translation, 0.15, color);
+
+        if let Some(tween_move) = &object.tween_move {
+            gizmos.line(
+                transform.translation,
+                tween_move.target,
+                color,
+            );
+            gizmos.sphere(tween_move.target, 0.1, color);
+        }
+
+        if session.selected == Some(entity) {
+            handles::draw(&mut gizmos, transform, session.tool);
+        }
+    }
+
+    for fuel_pickup in session.template.fuel_pickups.iter() {
+        gizmos.sphere(
+            fuel_pickup.translation,
+            0.3,
+            Color::srgb(0.2, 0.8, 0.3),
+        );
+    }
+}
+
+fn update_status_text(
+    session: Res<EditorSession>,
+    object_query: Query<(&EditorObject, &Transform)>,
+    mut text: Single<&mut Text, With<EditorText>>,
+) {
+    let selected = session
+        .selected
+        .and_then(|selected| object_query.get(selected).ok())
+        .map(|(object, transform)| {
+            let tween_move = object
+                .tween_move
+                .as_ref()
+                .map(|tween_move| {
+                    format!(
+                        " tween to {:.1} in {}s",
+                        tween_move.target, tween_move.duration
+                    )
+                })
+                .unwrap_or_default();
+
+            format!(
+                "{} ({:?}) at {:.1}{tween_move}",
+                object.asset, object.tag, transform.translation
+            )
+        })
+        .unwrap_or_else(|| "nothing".to_string());
+    let new_asset = session
+        .assets
+        .get(session.asset_index)
+        .map(String::as_str)
+        .unwrap_or("-");
+
+    let unsaved = if session.is_modified {
+        " (unsaved)"
+    } else {
+        ""
+    };
+
+    text.0 = [
+        format!(
+            "Editing {:?}{unsaved}",
+            LEVELS_ORDER[session.level_index]
+        ),
+        format!("Selected: {selected}"),
+        format!("Tool: {:?}  New: {new_asset}", session.tool),
+        session.message.clone(),
+        HELP.to_string(),
+    ]
+    .join("\n");
+}
+
+fn despawn(
+    mut commands: Commands,
+    object_query: Query<Entity, With<EditorObject>>,
+    text_query: Query<Entity, With<EditorText>>,
+) {
+    for entity in object_query.iter().chain(text_query.iter()) {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<EditorSession>();
+}
+
+fn is_shift_pressed(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+}
+
+fn is_ctrl_pressed(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+    ])
+}
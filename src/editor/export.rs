@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use bevy::{asset::io::file::FileAssetReader, prelude::*};
+use ron::ser::PrettyConfig;
+use thiserror::Error;
+
+use crate::{
+    editor::{EditorObject, EditorTag},
+    level::{Level, LevelDefinition, LevelObject},
+    save::write_file_atomically,
+};
+
+// Same folder the asset server reads from, so saving also hot-reloads
+// the level. Written through a temporary file, the watcher must never
+// load it half written
+const ASSETS_DIR: &str = "assets";
+
+#[derive(Debug, Error)]
+pub enum EditorError {
+    #[error("The level needs a {0:?} object")]
+    MissingObject(EditorTag),
+    #[error("The level can only have one {0:?} object")]
+    DuplicateObject(EditorTag),
+    #[error("Could not write level file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not serialize level file: {0}")]
+    Serialize(#[from] ron::Error),
+}
+
+// Sorted by entity, so obstacles keep a stable order between saves
+pub fn build_definition<'a>(
+    template: &LevelDefinition,
+    objects: impl Iterator<
+        Item = (Entity, &'a EditorObject, &'a Transform),
+    >,
+) -> Result<LevelDefinition, EditorError> {
+    let mut objects: Vec<_> = objects.collect();
+    objects.sort_by_key(|(entity, _, _)| *entity);
+
+    let mut definition = template.clone();
+    definition.obstacles.clear();
+
+    let mut floor = None;
+    let mut launch_pad = None;
+    let mut landing_pad = None;
+
+    for (_, object, transform) in objects {
+        let level_object = LevelObject {
+            asset: object.asset.clone(),
+            transform: transform.into(),
+            tween_move: object.tween_move.clone(),
+        };
+        let slot = match object.tag {
+            EditorTag::Floor => &mut floor,
+            EditorTag::LaunchPad => &mut launch_pad,
+            EditorTag::Finish => &mut landing_pad,
+            EditorTag::Obstacle => {
+                definition.obstacles.push(level_object);
+                continue;
+            }
+        };
+
+        if slot.replace(level_object).is_some() {
+            return Err(EditorError::DuplicateObject(
+                object.tag,
+            ));
+        }
+    }
+
+    definition.floor = floor
+        .ok_or(EditorError::MissingObject(EditorTag::Floor))?;
+    definition.launch_pad = launch_pad.ok_or(
+        EditorError::MissingObject(EditorTag::LaunchPad),
+    )?;
+    definition.landing_pad = landing_pad
+        .ok_or(EditorError::MissingObject(EditorTag::Finish))?;
+
+    Ok(definition)
+}
+
+pub fn save_definition(
+    level: Level,
+    definition: &LevelDefinition,
+) -> Result<PathBuf, EditorError> {
+    let path = FileAssetReader::new(ASSETS_DIR)
+        .root_path()
+        .join(level.definition_path());
+    let content = ron::ser::to_string_pretty(
+        definition,
+        PrettyConfig::default(),
+    )?;
+
+    write_file_atomically(&path, &content)?;
+
+    Ok(path)
+}
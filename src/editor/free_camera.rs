@@ -0,0 +1,68 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    input::mouse::{
+        AccumulatedMouseMotion, AccumulatedMouseScroll,
+    },
+    prelude::*,
+};
+
+use crate::editor::is_ctrl_pressed;
+
+const FLY_SPEED: f32 = 8.0;
+const LOOK_SENSITIVITY: f32 = 0.003;
+const ZOOM_STEP: f32 = 1.0;
+// Just short of straight up or down, where yaw flips around
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+// The camera rig is paused in the editor, this one moves the same
+// camera freely instead
+pub(super) fn fly(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    mouse_scroll: Res<AccumulatedMouseScroll>,
+    mut camera: Single<&mut Transform, With<Camera3d>>,
+    time: Res<Time>,
+) {
+    if mouse.pressed(MouseButton::Right)
+        && mouse_motion.delta != Vec2::ZERO
+    {
+        let (yaw, pitch, _) =
+            camera.rotation.to_euler(EulerRot::YXZ);
+        let yaw = yaw - mouse_motion.delta.x * LOOK_SENSITIVITY;
+        let pitch = (pitch
+            - mouse_motion.delta.y * LOOK_SENSITIVITY)
+            .clamp(-MAX_PITCH, MAX_PITCH);
+
+        camera.rotation =
+            Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+    }
+
+    let forward = *camera.forward();
+    let right = *camera.right();
+
+    // Ctrl+S saves the level instead
+    if !is_ctrl_pressed(&keys) {
+        let directions = [
+            (KeyCode::KeyW, forward),
+            (KeyCode::KeyS, -forward),
+            (KeyCode::KeyD, right),
+            (KeyCode::KeyA, -right),
+            (KeyCode::KeyE, Vec3::Y),
+            (KeyCode::KeyQ, Vec3::NEG_Y),
+        ];
+        let direction: Vec3 = directions
+            .into_iter()
+            .filter(|(key, _)| keys.pressed(*key))
+            .map(|(_, direction)| direction)
+            .sum();
+
+        camera.translation += direction.normalize_or_zero()
+            * FLY_SPEED
+            * time.delta_secs();
+    }
+
+    camera.translation +=
+        forward * mouse_scroll.delta.y * ZOOM_STEP;
+}
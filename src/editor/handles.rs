@@ -0,0 +1,368 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::editor::{EditorObject, EditorSession, EditorTool};
+
+const HANDLE_LENGTH: f32 = 1.5;
+const RING_RADIUS: f32 = 1.2;
+const SCALE_BOX_SIZE: f32 = 0.2;
+// How close the cursor ray has to pass to grab a handle or an object
+const PICK_RADIUS: f32 = 0.25;
+// Dragging along an axis that points at the camera, or around one
+// that lies flat to it, would jump around
+const MIN_AXIS_ANGLE_SIN: f32 = 0.05;
+const MIN_SCALE: f32 = 0.01;
+
+const AXES: [(Vec3, Color); 3] = [
+    (Vec3::X, Color::srgb(1.0, 0.2, 0.2)),
+    (Vec3::Y, Color::srgb(0.2, 1.0, 0.2)),
+    (Vec3::Z, Color::srgb(0.2, 0.4, 1.0)),
+];
+
+#[derive(Debug, Clone, Copy)]
+enum DragHandle {
+    // Moves along a world axis
+    Axis(Vec3),
+    // Turns around a world axis
+    Ring(Vec3),
+    // Stretches along a local axis of the object
+    Scale(Vec3),
+    // The object itself, moved on the plane facing the camera
+    Center(Dir3),
+}
+
+impl DragHandle {
+    // Where the ray meets the handle, for an object at `transform`
+    fn point(
+        self,
+        ray: Ray3d,
+        transform: &Transform,
+    ) -> Option<Vec3> {
+        let origin = transform.translation;
+
+        match self {
+            DragHandle::Axis(axis) => {
+                closest_point_on_axis(ray, origin, axis)
+            }
+            DragHandle::Scale(axis) => closest_point_on_axis(
+                ray,
+                origin,
+                transform.rotation * axis,
+            ),
+            DragHandle::Ring(axis) => {
+                if ray.direction.dot(axis).abs()
+                    < MIN_AXIS_ANGLE_SIN
+                {
+                    return None;
+                }
+
+                intersect_plane(ray, origin, axis)
+            }
+            DragHandle::Center(normal) => {
+                intersect_plane(ray, origin, *normal)
+            }
+        }
+    }
+
+    // The transform once the handle is dragged from `start_point` to
+    // `point`
+    fn apply(
+        self,
+        start: &Transform,
+        start_point: Vec3,
+        point: Vec3,
+    ) -> Transform {
+        let origin = start.translation;
+        let mut transform = *start;
+
+        match self {
+            DragHandle::Axis(_) | DragHandle::Center(_) => {
+                transform.translation += point - start_point;
+            }
+            DragHandle::Ring(axis) => {
+                let from = start_point - origin;
+                let to = point - origin;
+                let angle =
+                    from.cross(to).dot(axis).atan2(from.dot(to));
+
+                transform.rotation =
+                    Quat::from_axis_angle(axis, angle)
+                        * start.rotation;
+            }
+            DragHandle::Scale(axis) => {
+                let world_axis = start.rotation * axis;
+                let factor = (point - origin).dot(world_axis)
+                    / (start_point - origin).dot(world_axis);
+
+                transform.scale = (start.scale
+                    * (Vec3::ONE + axis * (factor - 1.0)))
+                    .max(Vec3::splat(MIN_SCALE));
+            }
+        }
+
+        transform
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Drag {
+    entity: Entity,
+    handle: DragHandle,
+    start_transform: Transform,
+    start_point: Vec3,
+}
+
+// Arrows to move, rings to rotate and boxes to scale, following the
+// picked tool
+pub(super) fn draw(
+    gizmos: &mut Gizmos,
+    transform: &Transform,
+    tool: EditorTool,
+) {
+    let translation = transform.translation;
+
+    for (axis, color) in AXES {
+        match tool {
+            EditorTool::Move | EditorTool::TweenTarget => {
+                gizmos.arrow(
+                    translation,
+                    translation + axis * HANDLE_LENGTH,
+                    color,
+                );
+            }
+            EditorTool::Rotate => {
+                gizmos.circle(
+                    Isometry3d::new(
+                        translation,
+                        Quat::from_rotation_arc(Vec3::Z, axis),
+                    ),
+                    RING_RADIUS,
+                    color,
+                );
+            }
+            EditorTool::Scale => {
+                let tip = translation
+                    + transform.rotation * axis * HANDLE_LENGTH;
+
+                gizmos.line(translation, tip, color);
+                gizmos.cuboid(
+                    Transform::from_translation(tip)
+                        .with_rotation(transform.rotation)
+                        .with_scale(Vec3::splat(SCALE_BOX_SIZE)),
+                    color,
+                );
+            }
+        }
+    }
+}
+
+// Grabs a handle of the selected object or any object under the cursor,
+// then moves, rotates or scales it with the mouse until the button is
+// released
+pub(super) fn drag(
+    mouse: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut session: ResMut<EditorSession>,
+    mut object_query: Query<
+        (Entity, &mut Transform),
+        With<EditorObject>,
+    >,
+) {
+    if !mouse.pressed(MouseButton::Left) {
+        if session.drag.is_some() {
+            session.drag = None;
+        }
+        return;
+    }
+
+    let (camera, camera_transform) = *camera;
+    let Some(ray) =
+        window.cursor_position().and_then(|cursor| {
+            camera
+                .viewport_to_world(camera_transform, cursor)
+                .ok()
+        })
+    else {
+        return;
+    };
+
+    if mouse.just_pressed(MouseButton::Left) {
+        session.drag = pick(
+            ray,
+            camera_transform.forward(),
+            session.tool,
+            session.selected,
+            &object_query,
+        );
+        if let Some(drag) = session.drag {
+            session.selected = Some(drag.entity);
+        }
+        return;
+    }
+
+    let Some(drag) = session.drag else {
+        return;
+    };
+    let Ok((_, mut transform)) =
+        object_query.get_mut(drag.entity)
+    else {
+        session.drag = None;
+        return;
+    };
+    let Some(point) =
+        drag.handle.point(ray, &drag.start_transform)
+    else {
+        return;
+    };
+
+    let dragged = drag.handle.apply(
+        &drag.start_transform,
+        drag.start_point,
+        point,
+    );
+    if dragged != *transform {
+        *transform = dragged;
+        session.is_modified = true;
+    }
+}
+
+// The handles of the selected object come first, they sit on top of it
+fn pick(
+    ray: Ray3d,
+    camera_forward: Dir3,
+    tool: EditorTool,
+    selected: Option<Entity>,
+    object_query: &Query<
+        (Entity, &mut Transform),
+        With<EditorObject>,
+    >,
+) -> Option<Drag> {
+    let start_drag =
+        |entity: Entity,
+         handle: DragHandle,
+         transform: &Transform| {
+            handle.point(ray, transform).map(|start_point| {
+                Drag {
+                    entity,
+                    handle,
+                    start_transform: *transform,
+                    start_point,
+                }
+            })
+        };
+
+    if let Some((entity, transform)) =
+        selected.and_then(|entity| object_query.get(entity).ok())
+        && let Some(handle) = pick_handle(ray, tool, transform)
+    {
+        return start_drag(entity, handle, transform);
+    }
+
+    object_query
+        .iter()
+        .filter(|(_, transform)| {
+            ray_distance(ray, transform.translation)
+                <= PICK_RADIUS
+        })
+        .min_by(|(_, a), (_, b)| {
+            let a = a.translation.distance_squared(ray.origin);
+            let b = b.translation.distance_squared(ray.origin);
+            a.total_cmp(&b)
+        })
+        .and_then(|(entity, transform)| {
+            start_drag(
+                entity,
+                DragHandle::Center(camera_forward),
+                transform,
+            )
+        })
+}
+
+// The handle of the tool under the cursor, the closest ring wins as
+// they cross each other
+fn pick_handle(
+    ray: Ray3d,
+    tool: EditorTool,
+    transform: &Transform,
+) -> Option<DragHandle> {
+    let translation = transform.translation;
+
+    match tool {
+        EditorTool::Move | EditorTool::TweenTarget => AXES
+            .into_iter()
+            .find(|(axis, _)| {
+                ray_distance(
+                    ray,
+                    translation + *axis * HANDLE_LENGTH,
+                ) <= PICK_RADIUS
+            })
+            .map(|(axis, _)| DragHandle::Axis(axis)),
+        EditorTool::Scale => AXES
+            .into_iter()
+            .find(|(axis, _)| {
+                ray_distance(
+                    ray,
+                    translation
+                        + transform.rotation
+                            * *axis
+                            * HANDLE_LENGTH,
+                ) <= PICK_RADIUS
+            })
+            .map(|(axis, _)| DragHandle::Scale(axis)),
+        EditorTool::Rotate => AXES
+            .into_iter()
+            .filter_map(|(axis, _)| {
+                let point =
+                    intersect_plane(ray, translation, axis)?;
+                let ring_distance =
+                    (point.distance(translation) - RING_RADIUS)
+                        .abs();
+
+                (ring_distance <= PICK_RADIUS).then_some((
+                    axis,
+                    point.distance_squared(ray.origin),
+                ))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(axis, _)| DragHandle::Ring(axis)),
+    }
+}
+
+// Closest point of the axis line through `origin` to the ray
+fn closest_point_on_axis(
+    ray: Ray3d,
+    origin: Vec3,
+    axis: Vec3,
+) -> Option<Vec3> {
+    let direction = *ray.direction;
+    let alignment = axis.dot(direction);
+    let denominator = 1.0 - alignment * alignment;
+    if denominator < MIN_AXIS_ANGLE_SIN.powi(2) {
+        return None;
+    }
+
+    let offset = origin - ray.origin;
+    let distance = (alignment * direction.dot(offset)
+        - axis.dot(offset))
+        / denominator;
+
+    Some(origin + axis * distance)
+}
+
+fn intersect_plane(
+    ray: Ray3d,
+    origin: Vec3,
+    normal: Vec3,
+) -> Option<Vec3> {
+    let normal = Dir3::new(normal).ok()?;
+
+    ray.intersect_plane(origin, InfinitePlane3d { normal })
+        .map(|distance| ray.get_point(distance))
+}
+
+fn ray_distance(ray: Ray3d, point: Vec3) -> f32 {
+    let distance =
+        (point - ray.origin).dot(*ray.direction).max(0.0);
+
+    ray.get_point(distance).distance(point)
+}
@@ -0,0 +1,299 @@
+use std::{fs, path::PathBuf};
+
+use bevy::{
+    pbr::NotShadowCaster, platform::collections::HashMap,
+    prelude::*, scene::SceneInstanceReady,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    AppState,
+    assets_loader::{ROCKET_MODEL, SceneAssets},
+    level::{CurrentLevel, Level, StartEvent, is_test_flight},
+    player::{
+        Player, PlayerLanded, ReplayPlayback, is_replaying,
+    },
+    save::{SavePath, Settings, write_file_atomically},
+};
+
+const GHOSTS_FILE_NAME: &str = "ghosts.ron";
+
+// Transforms of the fastest landing on a level, one per fixed tick
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GhostTrace {
+    time: f32,
+    samples: Vec<(Vec3, Quat)>,
+}
+
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+struct BestTraces(HashMap<Level, GhostTrace>);
+
+#[derive(Debug, Error)]
+enum GhostError {
+    #[error("Could not access ghosts file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse ghosts file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Could not serialize ghosts file: {0}")]
+    Serialize(#[from] ron::Error),
+}
+
+#[derive(Component, Default)]
+struct GhostRecorder(Vec<(Vec3, Quat)>);
+
+#[derive(Component)]
+struct Ghost {
+    trace: GhostTrace,
+    tick: usize,
+}
+
+#[derive(Resource)]
+struct GhostMaterial(Handle<StandardMaterial>);
+
+impl FromWorld for GhostMaterial {
+    fn from_world(world: &mut World) -> Self {
+        Self(
+            world
+                .resource_mut::<Assets<StandardMaterial>>()
+                .add(StandardMaterial {
+                    base_color: Color::srgba(
+                        0.6, 0.8, 1.0, 0.35,
+                    ),
+                    alpha_mode: AlphaMode::Blend,
+                    unlit: true,
+                    ..default()
+                }),
+        )
+    }
+}
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BestTraces>()
+            .init_resource::<GhostMaterial>()
+            .add_systems(OnEnter(AppState::Setup), load)
+            .add_systems(
+                Update,
+                (
+                    keep_best_trace
+                        .run_if(on_event::<PlayerLanded>)
+                        .run_if(not(is_replaying))
+                        .run_if(not(is_test_flight)),
+                    despawn_when_disabled
+                        .run_if(resource_changed::<Settings>),
+                ),
+            )
+            // Spawned with the player, so both move from the same tick
+            .add_systems(
+                FixedPreUpdate,
+                (despawn, spawn)
+                    .chain()
+                    .run_if(on_event::<StartEvent>)
+                    .run_if(not(in_state(AppState::Setup))),
+            )
+            // The recorder is added on the first tick of the player, so
+            // the trace starts on the same tick as the ghost
+            .add_systems(
+                FixedUpdate,
+                (
+                    (start_recording, record_sample).chain(),
+                    move_ghost,
+                )
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn ghosts_path(save_path: &SavePath) -> PathBuf {
+    save_path.0.with_file_name(GHOSTS_FILE_NAME)
+}
+
+fn load(mut commands: Commands, save_path: Res<SavePath>) {
+    let path = ghosts_path(&save_path);
+    if !path.exists() {
+        return;
+    }
+
+    let best_traces: Result<BestTraces, GhostError> =
+        fs::read_to_string(&path)
+            .map_err(GhostError::from)
+            .and_then(|content| Ok(ron::from_str(&content)?));
+
+    match best_traces {
+        Ok(best_traces) => commands.insert_resource(best_traces),
+        Err(err) => error!("{err}"),
+    }
+}
+
+fn save(
+    save_path: &SavePath,
+    best_traces: &BestTraces,
+) -> Result<(), GhostError> {
+    let path = ghosts_path(save_path);
+    let content = ron::to_string(best_traces)?;
+
+    write_file_atomically(&path, &content)?;
+
+    Ok(())
+}
+
+// Replays follow a run that was already traced
+fn start_recording(
+    mut commands: Commands,
+    player_query: Query<
+        Entity,
+        (Added<Player>, Without<ReplayPlayback>),
+    >,
+) {
+    for entity in player_query.iter() {
+        commands.entity(entity).insert(GhostRecorder::default());
+    }
+}
+
+fn record_sample(
+    mut player: Single<
+        (&Transform, &mut GhostRecorder),
+        With<Player>,
+    >,
+) {
+    let (transform, recorder) = &mut *player;
+
+    recorder.0.push((transform.translation, transform.rotation));
+}
+
+fn keep_best_trace(
+    mut er_landed: EventReader<PlayerLanded>,
+    mut best_traces: ResMut<BestTraces>,
+    recorder: Single<&GhostRecorder, With<Player>>,
+    current_level: Res<CurrentLevel>,
+    save_path: Res<SavePath>,
+) {
+    let Some(ev) = er_landed.read().last() else {
+        return;
+    };
+    let level = current_level.level();
+
+    if best_traces
+        .0
+        .get(&level)
+        .is_some_and(|trace| trace.time <= ev.time)
+    {
+        return;
+    }
+
+    best_traces.0.insert(
+        level,
+        GhostTrace {
+            time: ev.time,
+            samples: recorder.0.clone(),
+        },
+    );
+
+    if let Err(err) = save(&save_path, &best_traces) {
+        error!("{err}");
+    }
+}
+
+fn spawn(
+    mut commands: Commands,
+    best_traces: Res<BestTraces>,
+    scene_assets: Res<SceneAssets>,
+    current_level: Res<CurrentLevel>,
+    settings: Res<Settings>,
+) {
+    if !settings.is_ghost_enabled {
+        return;
+    }
+
+    let (Some(trace), Some(rocket)) = (
+        best_traces.0.get(&current_level.level()),
+        scene_assets.get_game_asset(ROCKET_MODEL),
+    ) else {
+        return;
+    };
+    let Some((translation, rotation)) = trace.samples.first()
+    else {
+        return;
+    };
+
+    // No collider, the ghost only shows the way
+    commands
+        .spawn((
+            SceneRoot(rocket.model.clone()),
+            Transform::from_translation(*translation)
+                .with_rotation(*rotation),
+            Name::new("Ghost"),
+            Ghost {
+                trace: trace.clone(),
+                tick: 0,
+            },
+        ))
+        .observe(make_translucent);
+}
+
+fn make_translucent(
+    trigger: Trigger<SceneInstanceReady>,
+    mut commands: Commands,
+    children_query: Query<&Children>,
+    mesh_query: Query<
+        (),
+        With<MeshMaterial3d<StandardMaterial>>,
+    >,
+    ghost_material: Res<GhostMaterial>,
+) {
+    for entity in
+        children_query.iter_descendants(trigger.target())
+    {
+        if mesh_query.get(entity).is_ok() {
+            commands.entity(entity).insert((
+                MeshMaterial3d(ghost_material.0.clone()),
+                NotShadowCaster,
+            ));
+        }
+    }
+}
+
+fn move_ghost(
+    mut ghost_query: Query<(&mut Transform, &mut Ghost)>,
+) {
+    for (mut transform, mut ghost) in ghost_query.iter_mut() {
+        // Stays on the landing pad once the trace is over
+        let Some((translation, rotation)) = ghost
+            .trace
+            .samples
+            .get(ghost.tick)
+            .or(ghost.trace.samples.last())
+            .copied()
+        else {
+            continue;
+        };
+
+        transform.translation = translation;
+        transform.rotation = rotation;
+        ghost.tick += 1;
+    }
+}
+
+fn despawn(
+    mut commands: Commands,
+    ghost_query: Query<Entity, With<Ghost>>,
+) {
+    for entity in ghost_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+// Turned off from the menu mid-try, turning it back on only shows it
+// from the next try
+fn despawn_when_disabled(
+    commands: Commands,
+    ghost_query: Query<Entity, With<Ghost>>,
+    settings: Res<Settings>,
+) {
+    if !settings.is_ghost_enabled {
+        despawn(commands, ghost_query);
+    }
+}
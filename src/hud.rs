@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+
+use crate::{
+    AppState,
+    collision::{ColliderAabb, LinearVelocity},
+    controls::Action,
+    level::{CurrentLevel, Floor},
+    player::{
+        CrashReason, Fuel, Player, PlayerCrashed, PlayerLanded,
+        RunStats, TriesCounter,
+    },
+    save::Settings,
+};
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    update_visibility
+                        .run_if(state_changed::<AppState>),
+                    update_stats,
+                    show_crash_banner
+                        .run_if(on_event::<PlayerCrashed>),
+                    show_landing_banner
+                        .run_if(on_event::<PlayerLanded>),
+                ),
+            )
+            .add_systems(
+                OnExit(AppState::Failed),
+                despawn_banner,
+            )
+            .add_systems(
+                OnExit(AppState::Succeed),
+                despawn_banner,
+            );
+    }
+}
+
+#[derive(Component)]
+struct Hud;
+
+#[derive(Component)]
+struct StatsText;
+
+#[derive(Component)]
+struct ResultBanner;
+
+fn setup(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(12.0),
+                left: Val::Px(12.0),
+                ..default()
+            },
+            Visibility::Hidden,
+            Name::new("HUD"),
+            Hud,
+        ))
+        .with_children(|hud| {
+            hud.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                StatsText,
+            ));
+        });
+}
+
+// Only shown while a level is being played or its result is displayed
+fn update_visibility(
+    state: Res<State<AppState>>,
+    mut hud_visibility: Single<&mut Visibility, With<Hud>>,
+) {
+    **hud_visibility = match state.get() {
+        AppState::InGame
+        | AppState::Paused
+        | AppState::Failed
+        | AppState::Succeed => Visibility::Inherited,
+        _ => Visibility::Hidden,
+    };
+}
+
+type PlayerStats<'a> = (
+    &'a GlobalTransform,
+    &'a LinearVelocity,
+    &'a Fuel,
+    &'a RunStats,
+);
+
+fn update_stats(
+    mut stats_text: Single<&mut Text, With<StatsText>>,
+    player: Option<Single<PlayerStats, With<Player>>>,
+    floor_query: Query<&ColliderAabb, With<Floor>>,
+    current_level: Res<CurrentLevel>,
+    tries_counter: Res<TriesCounter>,
+) {
+    let mut lines = vec![
+        format!("Level {}", current_level.index + 1),
+        format!("Tries: {}", tries_counter.0),
+    ];
+
+    if let Some(player) = player {
+        let (transform, linear_velocity, fuel, run_stats) =
+            *player;
+        let floor_height = floor_query
+            .iter()
+            .next()
+            .map_or(0.0, |floor_aabb| floor_aabb.max.y);
+
+        lines.extend([
+            format!("Time: {:.2}s", run_stats.elapsed_secs),
+            format!("Vertical speed: {:.1}", linear_velocity.y),
+            format!(
+                "Horizontal speed: {:.1}",
+                linear_velocity.x
+            ),
+            format!(
+                "Altitude: {:.1}",
+                transform.translation().y - floor_height
+            ),
+            format!("Fuel: {:.0}/{:.0}", fuel.current, fuel.max),
+        ]);
+    }
+
+    stats_text.0 = lines.join("\n");
+}
+
+fn show_crash_banner(
+    commands: Commands,
+    mut er_crashed: EventReader<PlayerCrashed>,
+    settings: Res<Settings>,
+) {
+    let Some(ev) = er_crashed.read().last() else {
+        return;
+    };
+    let reason = match ev.reason {
+        CrashReason::Obstacle => "Crashed into an obstacle",
+        CrashReason::HardLanding => "Landed too hard",
+        CrashReason::TiltedLanding => "Landed tilted",
+    };
+
+    spawn_banner(
+        commands,
+        format!(
+            "{reason}\nPress {} to retry",
+            settings.bindings.key_name(Action::Confirm)
+        ),
+        Color::srgb(0.9, 0.3, 0.25),
+    );
+}
+
+fn show_landing_banner(
+    commands: Commands,
+    mut er_landed: EventReader<PlayerLanded>,
+    settings: Res<Settings>,
+) {
+    let Some(ev) = er_landed.read().last() else {
+        return;
+    };
+
+    spawn_banner(
+        commands,
+        format!(
+            "Landed in {:.2}s\nPress {} to continue",
+            ev.time,
+            settings.bindings.key_name(Action::Confirm)
+        ),
+        Color::srgb(0.3, 0.85, 0.4),
+    );
+}
+
+fn spawn_banner(
+    mut commands: Commands,
+    message: String,
+    color: Color,
+) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Name::new("Result banner"),
+            ResultBanner,
+        ))
+        .with_children(|banner| {
+            banner.spawn((
+                Text::new(message),
+                TextFont {
+                    font_size: 36.0,
+                    ..default()
+                },
+                TextColor(color),
+                TextLayout::new_with_justify(
+                    JustifyText::Center,
+                ),
+            ));
+        });
+}
+
+fn despawn_banner(
+    mut commands: Commands,
+    query: Query<Entity, With<ResultBanner>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,161 @@
+use bevy::{input::keyboard::KeyboardInput, prelude::*};
+
+use crate::{
+    AppState,
+    level::CurrentLevel,
+    save::{LevelRecords, NewLeaderboardEntry},
+};
+
+const MAX_NAME_LENGTH: usize = 12;
+const DEFAULT_NAME: &str = "Player";
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Succeed), setup)
+            .add_systems(
+                Update,
+                (
+                    enter_name.run_if(
+                        resource_exists::<NewLeaderboardEntry>,
+                    ),
+                    update_leaderboard_text,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Succeed)),
+            )
+            .add_systems(OnExit(AppState::Succeed), despawn);
+    }
+}
+
+#[derive(Component)]
+struct LeaderboardPanel;
+
+#[derive(Component)]
+struct LeaderboardText;
+
+fn setup(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                bottom: Val::Px(48.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Name::new("Leaderboard"),
+            LeaderboardPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextLayout::new_with_justify(
+                    JustifyText::Center,
+                ),
+                LeaderboardText,
+            ));
+        });
+}
+
+fn enter_name(
+    mut commands: Commands,
+    mut er_keyboard: EventReader<KeyboardInput>,
+    mut new_entry: ResMut<NewLeaderboardEntry>,
+    mut records: ResMut<LevelRecords>,
+) {
+    for ev in er_keyboard.read() {
+        // Held keys would fill the name with repeats
+        if !ev.state.is_pressed() || ev.repeat {
+            continue;
+        }
+
+        match ev.key_code {
+            KeyCode::Enter => {
+                let mut entry = new_entry.entry.clone();
+                entry.name = entry.name.trim().to_string();
+                if entry.name.is_empty() {
+                    entry.name = DEFAULT_NAME.to_string();
+                }
+
+                records
+                    .0
+                    .entry(new_entry.level)
+                    .or_default()
+                    .add_to_leaderboard(entry);
+                commands
+                    .remove_resource::<NewLeaderboardEntry>();
+                return;
+            }
+            KeyCode::Backspace => {
+                new_entry.entry.name.pop();
+            }
+            _ => {
+                let Some(text) = &ev.text else {
+                    continue;
+                };
+                let name = &mut new_entry.entry.name;
+
+                for character in
+                    text.chars().filter(|c| !c.is_control())
+                {
+                    if name.chars().count() < MAX_NAME_LENGTH {
+                        name.push(character);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn update_leaderboard_text(
+    mut leaderboard_text: Single<
+        &mut Text,
+        With<LeaderboardText>,
+    >,
+    records: Res<LevelRecords>,
+    new_entry: Option<Res<NewLeaderboardEntry>>,
+    current_level: Res<CurrentLevel>,
+) {
+    let mut lines = vec!["Leaderboard".to_string()];
+
+    if let Some(record) = records.0.get(&current_level.level()) {
+        lines.extend(record.leaderboard.iter().enumerate().map(
+            |(index, entry)| {
+                format!(
+                    "{}. {}  {:.2}s  {} tries  {:.0} fuel",
+                    index + 1,
+                    entry.name,
+                    entry.time,
+                    entry.tries,
+                    entry.fuel_used
+                )
+            },
+        ));
+    }
+
+    if let Some(new_entry) = new_entry {
+        lines.push(format!(
+            "New record! Enter your name: {}_",
+            new_entry.entry.name
+        ));
+    }
+
+    leaderboard_text.0 = lines.join("\n");
+}
+
+fn despawn(
+    mut commands: Commands,
+    query: Query<Entity, With<LeaderboardPanel>>,
+) {
+    commands.remove_resource::<NewLeaderboardEntry>();
+
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
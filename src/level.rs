@@ -1,13 +1,25 @@
+mod definition;
 mod tween_move;
 
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    AppState,
     assets_loader::{GameAsset, SceneAssets},
-    collision::{RigidBody, create_collider},
-    level::tween_move::TweenMove,
+    collision::{
+        RigidBody, create_collider, create_sensor_collider,
+        create_static_material,
+    },
+    options::LaunchOptions,
+    player::is_replaying,
 };
 
+use definition::LevelDefinitionLoader;
+pub use definition::{
+    LevelDefinition, LevelObject, LevelTransform,
+};
+pub use tween_move::TweenMove;
 use tween_move::TweenMovePlugin;
 
 pub struct LevelPlugin;
@@ -15,17 +27,82 @@ pub struct LevelPlugin;
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<ChangeEvent>()
+            .add_event::<StartEvent>()
+            .init_asset::<LevelDefinition>()
+            .init_asset_loader::<LevelDefinitionLoader>()
+            .init_resource::<CurrentLevel>()
+            .init_resource::<UnlockedLevels>()
             .add_plugins(TweenMovePlugin)
+            .init_resource::<FuelPickupAssets>()
             .add_systems(Startup, setup)
-            .add_systems(Update, on_change);
+            // Runs before state transitions, so `OnEnter` systems
+            // always see the level requested on the previous frame
+            .add_systems(PreUpdate, on_change)
+            // Before the save on entering `Succeed`, so the unlock is
+            // never left out of the save file
+            .add_systems(
+                OnEnter(AppState::Succeed),
+                unlock_next_level
+                    .run_if(not(is_replaying))
+                    .run_if(not(is_test_flight)),
+            )
+            .add_systems(Update, on_level_assets_modified)
+            // On a fixed tick like the player, so obstacles and rocket
+            // always start a try together
+            .add_systems(
+                FixedPreUpdate,
+                spawn_level
+                    // Wait for the loading screen so the level never
+                    // spawns with unloaded models and colliders
+                    .run_if(not(in_state(AppState::Setup)))
+                    // The editor shows its own copy of the level
+                    .run_if(not(in_state(AppState::Editor))),
+            );
+
+        #[cfg(feature = "dev")]
+        app.add_systems(
+            Update,
+            skip_level
+                .run_if(
+                    in_state(AppState::InGame)
+                        .or(in_state(AppState::Paused))
+                        .or(in_state(AppState::Failed))
+                        .or(in_state(AppState::Succeed)),
+                )
+                // Page keys are left alone while a name is typed in
+                .run_if(not(resource_exists::<
+                    crate::save::NewLeaderboardEntry,
+                >)),
+        );
     }
 }
 
-fn setup(mut ew_change: EventWriter<ChangeEvent>) {
-    ew_change.write(ChangeEvent::Set(Level::First));
+#[cfg(feature = "dev")]
+const NEXT_LEVEL_KEY: KeyCode = KeyCode::PageDown;
+#[cfg(feature = "dev")]
+const PREVIOUS_LEVEL_KEY: KeyCode = KeyCode::PageUp;
+
+fn setup(
+    mut ew_change: EventWriter<ChangeEvent>,
+    options: Option<Res<LaunchOptions>>,
+) {
+    let level = options
+        .map(|options| options.start_level)
+        .unwrap_or(Level::First);
+
+    ew_change.write(ChangeEvent::Set(level));
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
 pub enum Level {
     First,
     Second,
@@ -33,11 +110,80 @@ pub enum Level {
     Fourth,
 }
 
-const LEVELS_ORDER: [Level; 4] =
+impl Level {
+    pub fn definition_path(&self) -> &'static str {
+        match self {
+            Level::First => "levels/first.level.ron",
+            Level::Second => "levels/second.level.ron",
+            Level::Third => "levels/third.level.ron",
+            Level::Fourth => "levels/fourth.level.ron",
+        }
+    }
+}
+
+pub const LEVELS_ORDER: [Level; 4] =
     [Level::First, Level::Second, Level::Third, Level::Fourth];
 
+#[derive(Resource, Debug, Default)]
+pub struct CurrentLevel {
+    pub index: usize,
+    is_spawned: bool,
+    // Played instead of the loaded definition, kept on reload
+    test_flight: Option<Handle<LevelDefinition>>,
+}
+
+impl CurrentLevel {
+    pub fn level(&self) -> Level {
+        LEVELS_ORDER[self.index]
+    }
+
+    pub fn definition_handle<'a>(
+        &'a self,
+        scene_assets: &'a SceneAssets,
+    ) -> &'a Handle<LevelDefinition> {
+        self.test_flight
+            .as_ref()
+            .unwrap_or(&scene_assets.levels[self.index])
+    }
+
+    pub fn definition<'a>(
+        &self,
+        scene_assets: &SceneAssets,
+        level_definitions: &'a Assets<LevelDefinition>,
+    ) -> Option<&'a LevelDefinition> {
+        level_definitions
+            .get(self.definition_handle(scene_assets))
+    }
+}
+
+// Levels unlock one by one in `LEVELS_ORDER`, the first is always open
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct UnlockedLevels {
+    count: usize,
+}
+
+impl Default for UnlockedLevels {
+    fn default() -> Self {
+        Self { count: 1 }
+    }
+}
+
+impl UnlockedLevels {
+    pub fn is_unlocked(&self, level: Level) -> bool {
+        LEVELS_ORDER
+            .iter()
+            .position(|l| *l == level)
+            .is_some_and(|index| index < self.count)
+    }
+
+    fn unlock_after(&mut self, index: usize) {
+        self.count =
+            self.count.max(index + 2).min(LEVELS_ORDER.len());
+    }
+}
+
 #[derive(Component)]
-struct LevelMarker;
+pub struct LevelMarker;
 
 #[derive(Component)]
 pub struct Obstacle;
@@ -45,27 +191,86 @@ pub struct Obstacle;
 #[derive(Component)]
 pub struct Finish;
 
+#[derive(Component)]
+pub struct Floor;
+
+#[derive(Component)]
+pub struct FuelPickup {
+    pub amount: f32,
+}
+
+const FUEL_PICKUP_RADIUS: f32 = 0.3;
+
+#[derive(Resource)]
+struct FuelPickupAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for FuelPickupAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Sphere::new(FUEL_PICKUP_RADIUS));
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color: Color::srgb(0.2, 0.8, 0.3),
+                emissive: LinearRgba::rgb(0.1, 0.6, 0.2),
+                ..default()
+            });
+
+        Self { mesh, material }
+    }
+}
+
 #[derive(Event, Debug)]
 pub enum ChangeEvent {
     Next,
+    Previous,
     Set(Level),
     Reload,
+    // Plays a definition that is not saved yet in place of the current
+    // level's, until another level is picked
+    TestFly(Handle<LevelDefinition>),
 }
 
-fn create_static_asset(game_asset: GameAsset) -> impl Bundle {
-    let collider = game_asset.collider.clone().unwrap();
+// Sent once a `ChangeEvent` is applied, a new try of the level begins
+#[derive(Event, Debug)]
+pub struct StartEvent(pub Level);
+
+// Assets without a collider are only scenery, nothing can hit them
+fn spawn_level_object<'a>(
+    level: &'a mut ChildSpawnerCommands,
+    scene_assets: &SceneAssets,
+    level_object: &LevelObject,
+) -> Option<EntityCommands<'a>> {
+    let Some(game_asset) =
+        scene_assets.get_game_asset(&level_object.asset)
+    else {
+        warn!("Unknown level asset: {}", level_object.asset);
+        return None;
+    };
 
-    (
+    let mut entity = level.spawn((
         SceneRoot(game_asset.model.clone()),
-        create_collider(RigidBody::Static, collider),
-    )
+        Transform::from(&level_object.transform),
+    ));
+    if let Some(collider) = game_asset.collider.clone() {
+        entity.insert((
+            create_collider(RigidBody::Static, collider),
+            create_static_material(),
+        ));
+    }
+
+    Some(entity)
 }
 
 fn on_change(
     mut commands: Commands,
     mut er_change: EventReader<ChangeEvent>,
-    mut current_level_index: Local<usize>,
-    scene_assets: Res<SceneAssets>,
+    mut ew_start: EventWriter<StartEvent>,
+    mut current_level: ResMut<CurrentLevel>,
     level_query: Query<Entity, With<LevelMarker>>,
 ) {
     for ev in er_change.read() {
@@ -74,93 +279,245 @@ fn on_change(
         }
         let level_to_load_index = match ev {
             ChangeEvent::Next => {
-                (*current_level_index + 1) % LEVELS_ORDER.len()
+                (current_level.index + 1) % LEVELS_ORDER.len()
+            }
+            ChangeEvent::Previous => {
+                (current_level.index + LEVELS_ORDER.len() - 1)
+                    % LEVELS_ORDER.len()
             }
             ChangeEvent::Set(level) => LEVELS_ORDER
                 .iter()
                 .position(|l| l == level)
                 .unwrap_or(0),
-            ChangeEvent::Reload => *current_level_index,
+            ChangeEvent::Reload | ChangeEvent::TestFly(_) => {
+                current_level.index
+            }
+        };
+
+        current_level.test_flight = match ev {
+            ChangeEvent::Reload => {
+                current_level.test_flight.take()
+            }
+            ChangeEvent::TestFly(handle) => Some(handle.clone()),
+            _ => None,
         };
+        current_level.index = level_to_load_index;
+        current_level.is_spawned = false;
+        ew_start.write(StartEvent(current_level.level()));
+    }
+}
 
-        *current_level_index = level_to_load_index;
+// Jumps between levels without beating them, nothing gets unlocked
+#[cfg(feature = "dev")]
+fn skip_level(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut ew_change: EventWriter<ChangeEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let change = if keys.just_pressed(NEXT_LEVEL_KEY) {
+        ChangeEvent::Next
+    } else if keys.just_pressed(PREVIOUS_LEVEL_KEY) {
+        ChangeEvent::Previous
+    } else {
+        return;
+    };
 
-        commands
-            .spawn((
-                Transform::from_xyz(0.0, 0.0, 0.0),
-                Visibility::default(),
-                LevelMarker,
-            ))
-            .with_children(|level| {
-                level.spawn((
-                    create_static_asset(
-                        scene_assets.floor.clone(),
-                    ),
+    ew_change.write(change);
+    next_state.set(AppState::InGame);
+}
+
+// Nothing a test flight of an unsaved layout does is kept either
+pub fn is_test_flight(current_level: Res<CurrentLevel>) -> bool {
+    current_level.test_flight.is_some()
+}
+
+pub fn unlock_next_level(
+    mut unlocked_levels: ResMut<UnlockedLevels>,
+    current_level: Res<CurrentLevel>,
+) {
+    unlocked_levels.unlock_after(current_level.index);
+}
+
+// Asset changes that can touch the spawned level
+#[derive(SystemParam)]
+struct LevelAssetEvents<'w, 's> {
+    level_definition:
+        EventReader<'w, 's, AssetEvent<LevelDefinition>>,
+    scene: EventReader<'w, 's, AssetEvent<Scene>>,
+    mesh: EventReader<'w, 's, AssetEvent<Mesh>>,
+}
+
+// Respawns the current level in place when its definition or any
+// referenced glTF changes on disk, leaving the player untouched
+fn on_level_assets_modified(
+    mut commands: Commands,
+    mut asset_events: LevelAssetEvents,
+    mut current_level: ResMut<CurrentLevel>,
+    scene_assets: Res<SceneAssets>,
+    level_definitions: Res<Assets<LevelDefinition>>,
+    level_query: Query<Entity, With<LevelMarker>>,
+) {
+    let current_definition_id =
+        current_level.definition_handle(&scene_assets).id();
+
+    // Every reader is drained, events left unread would trigger
+    // another reload on the next frame
+    let is_definition_modified = asset_events
+        .level_definition
+        .read()
+        .filter(|ev| ev.is_modified(current_definition_id))
+        .count()
+        > 0;
+
+    let game_assets: Vec<&GameAsset> = level_definitions
+        .get(current_definition_id)
+        .map(|definition| {
+            definition
+                .objects()
+                .filter_map(|level_object| {
+                    scene_assets
+                        .get_game_asset(&level_object.asset)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let is_scene_modified = asset_events
+        .scene
+        .read()
+        .filter(|ev| {
+            game_assets.iter().any(|game_asset| {
+                ev.is_modified(&game_asset.model)
+            })
+        })
+        .count()
+        > 0;
+    let is_mesh_modified = asset_events
+        .mesh
+        .read()
+        .filter(|ev| {
+            game_assets.iter().any(|game_asset| {
+                game_asset.collider.as_ref().is_some_and(
+                    |collider| ev.is_modified(collider),
+                )
+            })
+        })
+        .count()
+        > 0;
+
+    if !(is_definition_modified
+        || is_scene_modified
+        || is_mesh_modified)
+    {
+        return;
+    }
+
+    info!("Hot-reloading level {:?}", current_level.level());
+
+    for entity in level_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    current_level.is_spawned = false;
+}
+
+fn spawn_level(
+    mut commands: Commands,
+    mut current_level: ResMut<CurrentLevel>,
+    scene_assets: Res<SceneAssets>,
+    level_definitions: Res<Assets<LevelDefinition>>,
+    fuel_pickup_assets: Res<FuelPickupAssets>,
+) {
+    if current_level.is_spawned {
+        return;
+    }
+
+    // Level definition is loaded asynchronously, so wait until it's ready
+    let Some(definition) = current_level
+        .definition(&scene_assets, &level_definitions)
+    else {
+        return;
+    };
+
+    current_level.is_spawned = true;
+
+    commands
+        .spawn((
+            Transform::from_xyz(0.0, 0.0, 0.0),
+            Visibility::default(),
+            Name::new(format!(
+                "Level {:?}",
+                current_level.level()
+            )),
+            LevelMarker,
+        ))
+        .with_children(|level| {
+            if let Some(mut floor) = spawn_level_object(
+                level,
+                &scene_assets,
+                &definition.floor,
+            ) {
+                floor.insert((
                     Name::new("Floor"),
                     Obstacle,
+                    Floor,
                 ));
+            }
 
-                level.spawn((
-                    create_static_asset(
-                        scene_assets.landing_pad.clone(),
-                    ),
-                    Name::new("LandingPad"),
-                    Finish,
+            if let Some(mut landing_pad) = spawn_level_object(
+                level,
+                &scene_assets,
+                &definition.landing_pad,
+            ) {
+                landing_pad
+                    .insert((Name::new("LandingPad"), Finish));
+            }
+
+            if let Some(mut launch_pad) = spawn_level_object(
+                level,
+                &scene_assets,
+                &definition.launch_pad,
+            ) {
+                launch_pad.insert(Name::new("LaunchPad"));
+            }
+
+            for (index, obstacle) in
+                definition.obstacles.iter().enumerate()
+            {
+                let Some(mut entity) = spawn_level_object(
+                    level,
+                    &scene_assets,
+                    obstacle,
+                ) else {
+                    continue;
+                };
+
+                entity.insert((
+                    Name::new(format!("Obstacle_{index}")),
+                    Obstacle,
                 ));
 
+                if let Some(tween_move) = &obstacle.tween_move {
+                    entity.insert(tween_move.clone());
+                }
+            }
+
+            for (index, fuel_pickup) in
+                definition.fuel_pickups.iter().enumerate()
+            {
                 level.spawn((
-                    create_static_asset(
-                        scene_assets.launch_pad.clone(),
+                    Mesh3d(fuel_pickup_assets.mesh.clone()),
+                    MeshMaterial3d(
+                        fuel_pickup_assets.material.clone(),
                     ),
-                    Name::new("LaunchPad"),
+                    Transform::from_translation(
+                        fuel_pickup.translation,
+                    ),
+                    create_sensor_collider(FUEL_PICKUP_RADIUS),
+                    Name::new(format!("FuelPickup_{index}")),
+                    FuelPickup {
+                        amount: fuel_pickup.amount,
+                    },
                 ));
-
-                match LEVELS_ORDER[level_to_load_index] {
-                    Level::Second => {
-                        level.spawn((
-                            create_static_asset(
-                                scene_assets.obstacle_2.clone(),
-                            ),
-                            Name::new("2_Obstacle"),
-                            Obstacle,
-                        ));
-                    }
-                    Level::Third => {
-                        level.spawn((
-                            create_static_asset(
-                                scene_assets.obstacle_3.clone(),
-                            ),
-                            Name::new("3_Obstacle"),
-                            Obstacle,
-                        ));
-                    }
-                    Level::Fourth => {
-                        level.spawn((
-                            // TODO: Make own asset
-                            SceneRoot(
-                                scene_assets
-                                    .obstacle_2
-                                    .model
-                                    .clone(),
-                            ),
-                            create_collider(
-                                RigidBody::Static,
-                                scene_assets
-                                    .obstacle_2
-                                    .collider
-                                    .clone()
-                                    .unwrap(),
-                            ),
-                            Name::new("4_Obstacle"),
-                            Obstacle,
-                            TweenMove {
-                                target: Vec3::new(0.0, 2.5, 0.0),
-                                duration: 2,
-                            },
-                        ));
-                    }
-                    _ => {}
-                }
-            });
-    }
+            }
+        });
 }
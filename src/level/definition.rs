@@ -0,0 +1,147 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    camera::CameraBounds, level::tween_move::TweenMove,
+    player::RocketTuningOverride,
+};
+
+const DEFAULT_STARTING_FUEL: f32 = 100.0;
+
+#[derive(
+    Asset, TypePath, Debug, Clone, Serialize, Deserialize,
+)]
+pub struct LevelDefinition {
+    pub floor: LevelObject,
+    pub launch_pad: LevelObject,
+    pub landing_pad: LevelObject,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub obstacles: Vec<LevelObject>,
+    #[serde(default = "default_starting_fuel")]
+    pub starting_fuel: f32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fuel_pickups: Vec<FuelPickupDefinition>,
+    // Applied over the base rocket tuning while the level is active
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rocket_tuning: Option<RocketTuningOverride>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub camera_bounds: Option<CameraBounds>,
+}
+
+fn default_starting_fuel() -> f32 {
+    DEFAULT_STARTING_FUEL
+}
+
+impl LevelDefinition {
+    pub fn objects(&self) -> impl Iterator<Item = &LevelObject> {
+        [&self.floor, &self.launch_pad, &self.landing_pad]
+            .into_iter()
+            .chain(self.obstacles.iter())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelObject {
+    // Key of the `GameAsset` in `SceneAssets`
+    pub asset: String,
+    #[serde(default)]
+    pub transform: LevelTransform,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tween_move: Option<TweenMove>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuelPickupDefinition {
+    pub translation: Vec3,
+    pub amount: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelTransform {
+    pub translation: Vec3,
+    // Euler angles in degrees, applied in XYZ order
+    pub rotation: Vec3,
+    pub scale: Vec3,
+}
+
+impl Default for LevelTransform {
+    fn default() -> Self {
+        Self {
+            translation: Vec3::ZERO,
+            rotation: Vec3::ZERO,
+            scale: Vec3::ONE,
+        }
+    }
+}
+
+impl From<&LevelTransform> for Transform {
+    fn from(level_transform: &LevelTransform) -> Self {
+        let rotation = level_transform.rotation;
+
+        Transform {
+            translation: level_transform.translation,
+            rotation: Quat::from_euler(
+                EulerRot::XYZ,
+                rotation.x.to_radians(),
+                rotation.y.to_radians(),
+                rotation.z.to_radians(),
+            ),
+            scale: level_transform.scale,
+        }
+    }
+}
+
+impl From<&Transform> for LevelTransform {
+    fn from(transform: &Transform) -> Self {
+        let (x, y, z) =
+            transform.rotation.to_euler(EulerRot::XYZ);
+
+        LevelTransform {
+            translation: transform.translation,
+            rotation: Vec3::new(
+                x.to_degrees(),
+                y.to_degrees(),
+                z.to_degrees(),
+            ),
+            scale: transform.scale,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LevelDefinitionLoader;
+
+#[derive(Debug, Error)]
+pub enum LevelDefinitionLoaderError {
+    #[error("Could not read level definition: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse level definition: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for LevelDefinitionLoader {
+    type Asset = LevelDefinition;
+    type Settings = ();
+    type Error = LevelDefinitionLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        Ok(ron::de::from_bytes::<LevelDefinition>(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
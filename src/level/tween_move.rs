@@ -1,44 +1,62 @@
+use std::f32::consts::PI;
+
 use bevy::prelude::*;
-use bevy_tween::interpolate;
-use bevy_tween::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::player::{Player, RunStats};
 
-#[derive(Component)]
-#[require(Transform)]
+// Moves back and forth between where it was spawned and `target`,
+// taking `duration` seconds each way
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+#[require(Transform, TweenMoveStart)]
 pub struct TweenMove {
     pub target: Vec3,
     pub duration: usize,
 }
 
+#[derive(Component, Default)]
+struct TweenMoveStart(Option<Vec3>);
+
 pub struct TweenMovePlugin;
 
 impl Plugin for TweenMovePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(DefaultTweenPlugins)
-            .add_systems(Update, handle_move);
+        app.add_systems(FixedPreUpdate, handle_move);
     }
 }
 
+// Follows the fixed ticks of the current try instead of the frame time,
+// so a replay meets the obstacles at the same spots. Holds still while
+// paused, in the menus and once the try is over
 fn handle_move(
-    mut commands: Commands,
-    mut query: Query<(Entity, &TweenMove), Added<TweenMove>>,
+    mut query: Query<(
+        &TweenMove,
+        &mut TweenMoveStart,
+        &mut Transform,
+    )>,
+    run_stats: Option<Single<&RunStats, With<Player>>>,
 ) {
-    for (entity, move_config) in query.iter_mut() {
-        let target = entity.into_target();
-        let mut transform_state = target.state(Vec3::ZERO);
-
-        commands
-            .entity(entity)
-            .animation()
-            .repeat(Repeat::Infinitely)
-            .repeat_style(RepeatStyle::PingPong)
-            .insert_tween_here(
-                Duration::from_secs(move_config.duration as u64),
-                EaseKind::SineInOut,
-                transform_state.with(
-                    interpolate::translation_to(
-                        move_config.target,
-                    ),
-                ),
-            );
+    let elapsed_secs = run_stats
+        .map(|run_stats| run_stats.elapsed_secs)
+        .unwrap_or_default();
+
+    for (move_config, mut start, mut transform) in
+        query.iter_mut()
+    {
+        let start =
+            *start.0.get_or_insert(transform.translation);
+        let duration = move_config.duration as f32;
+        if duration <= 0.0 {
+            continue;
+        }
+
+        // Ping-pong between 0 and 1, eased in and out
+        let phase = (elapsed_secs / duration) % 2.0;
+        let progress =
+            if phase <= 1.0 { phase } else { 2.0 - phase };
+        let eased = (1.0 - (progress * PI).cos()) / 2.0;
+
+        transform.translation =
+            start.lerp(move_config.target, eased);
     }
 }
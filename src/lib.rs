@@ -0,0 +1,278 @@
+mod assets_loader;
+mod camera;
+pub mod collision;
+pub mod controls;
+#[cfg(feature = "editor")]
+pub mod editor;
+mod ghost;
+mod hud;
+mod leaderboard;
+pub mod level;
+mod loading;
+mod menu;
+pub mod options;
+pub mod player;
+pub mod save;
+pub mod simulation;
+#[cfg(feature = "desktop")]
+mod sounds;
+
+#[cfg(feature = "physics_debug")]
+use avian3d::prelude::PhysicsDebugPlugin;
+use bevy::{
+    app::PluginGroupBuilder, prelude::*, window::PresentMode,
+};
+#[cfg(feature = "inspector")]
+use bevy_inspector_egui::{
+    bevy_egui::EguiPlugin, quick::WorldInspectorPlugin,
+};
+#[cfg(feature = "perf_ui")]
+use iyes_perf_ui::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    camera::CameraPlugin,
+    controls::{Action, ControlsPlugin},
+    ghost::GhostPlugin,
+    hud::HudPlugin,
+    leaderboard::LeaderboardPlugin,
+    loading::LoadingPlugin,
+    menu::MenuPlugin,
+    save::{NewLeaderboardEntry, SavePlugin},
+    simulation::HeadlessPlugins,
+};
+
+pub use assets_loader::AssetLoaderPlugin;
+pub use collision::CollisionPlugin;
+pub use level::LevelPlugin;
+pub use player::PlayerPlugin;
+#[cfg(feature = "desktop")]
+pub use sounds::SoundsPlugin;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default, States)]
+pub enum AppState {
+    #[default]
+    Setup,
+    MainMenu,
+    LevelSelect,
+    InGame,
+    Paused,
+    Controls,
+    Failed,
+    Succeed,
+    Editor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GameMode {
+    #[default]
+    Windowed,
+    // No window, audio, rendering or menus, for simulations and tests
+    Headless,
+}
+
+// Only used with a window, they all draw on screen. Each one also needs
+// its cargo feature, otherwise enabling it only logs a warning
+#[derive(Debug, Clone, Default)]
+pub struct DevTools {
+    pub is_inspector_enabled: bool,
+    pub is_perf_ui_enabled: bool,
+    pub is_physics_debug_enabled: bool,
+}
+
+// The whole game, `main` and the tests only pick the configuration
+pub struct BoostGamePlugin {
+    pub mode: GameMode,
+    pub window: Window,
+    pub dev_tools: DevTools,
+}
+
+impl Default for BoostGamePlugin {
+    fn default() -> Self {
+        Self {
+            mode: GameMode::Windowed,
+            window: Window {
+                // Turn off vsync to maximize CPU/GPU usage
+                present_mode: PresentMode::AutoNoVsync,
+                ..default()
+            },
+            dev_tools: DevTools::default(),
+        }
+    }
+}
+
+impl BoostGamePlugin {
+    pub fn headless() -> Self {
+        Self {
+            mode: GameMode::Headless,
+            ..default()
+        }
+    }
+}
+
+// Every plugin can be swapped out with `.set()` or left out with
+// `.disable()`, like in bevy's `DefaultPlugins`
+impl PluginGroup for BoostGamePlugin {
+    fn build(self) -> PluginGroupBuilder {
+        let group = PluginGroupBuilder::start::<Self>();
+        let group = match self.mode {
+            GameMode::Windowed => {
+                let group = group
+                    .add_group(DefaultPlugins.set(
+                        WindowPlugin {
+                            primary_window: Some(self.window),
+                            ..default()
+                        },
+                    ))
+                    .add(WindowedPlugin)
+                    .add(CameraPlugin)
+                    .add(GhostPlugin)
+                    .add(HudPlugin)
+                    .add(LeaderboardPlugin)
+                    .add(MenuPlugin)
+                    .add(SavePlugin)
+                    .add(DevToolsPlugin(self.dev_tools));
+
+                #[cfg(feature = "desktop")]
+                let group = group.add(SoundsPlugin);
+                #[cfg(feature = "editor")]
+                let group = group.add(editor::EditorPlugin);
+
+                group
+            }
+            GameMode::Headless => {
+                group.add_group(HeadlessPlugins)
+            }
+        };
+
+        group
+            .add(AssetLoaderPlugin)
+            .add(CollisionPlugin)
+            .add(ControlsPlugin)
+            .add(LevelPlugin)
+            .add(LoadingPlugin)
+            .add(PlayerPlugin)
+    }
+}
+
+// The look of the scene and the shortcuts on the results screens, only
+// needed with a window
+struct WindowedPlugin;
+
+impl Plugin for WindowedPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(Color::srgb(
+            0.133, 0.12, 0.12,
+        )))
+        .init_state::<AppState>()
+        .insert_resource(AmbientLight {
+            brightness: 400.0,
+            ..default()
+        })
+        .add_systems(
+            Update,
+            on_results
+                .run_if(
+                    in_state(AppState::Failed)
+                        .or(in_state(AppState::Succeed)),
+                )
+                // Space is also typed into the leaderboard name
+                .run_if(not(resource_exists::<
+                    NewLeaderboardEntry,
+                >)),
+        )
+        .add_systems(
+            Update,
+            on_restart
+                .run_if(
+                    in_state(AppState::InGame)
+                        .or(in_state(AppState::Paused))
+                        .or(in_state(AppState::Failed))
+                        .or(in_state(AppState::Succeed)),
+                )
+                .run_if(not(resource_exists::<
+                    NewLeaderboardEntry,
+                >)),
+        );
+    }
+}
+
+struct DevToolsPlugin(DevTools);
+
+impl Plugin for DevToolsPlugin {
+    fn build(&self, app: &mut App) {
+        let dev_tools = &self.0;
+
+        // Every tool below only logs a warning in a release build
+        #[cfg(not(any(
+            feature = "inspector",
+            feature = "perf_ui",
+            feature = "physics_debug"
+        )))]
+        let _ = app;
+
+        if dev_tools.is_inspector_enabled {
+            #[cfg(feature = "inspector")]
+            app.add_plugins((
+                EguiPlugin::default(),
+                WorldInspectorPlugin::default(),
+            ));
+            #[cfg(not(feature = "inspector"))]
+            warn!("Built without the `inspector` feature");
+        }
+
+        if dev_tools.is_perf_ui_enabled {
+            #[cfg(feature = "perf_ui")]
+            app
+                .add_plugins(bevy::diagnostic::FrameTimeDiagnosticsPlugin::default())
+                .add_plugins(bevy::diagnostic::EntityCountDiagnosticsPlugin)
+                .add_plugins(bevy::diagnostic::SystemInformationDiagnosticsPlugin)
+                .add_plugins(bevy::render::diagnostic::RenderDiagnosticsPlugin)
+                .add_plugins(PerfUiPlugin)
+                .add_systems(OnEnter(AppState::Setup), spawn_perf_ui);
+            #[cfg(not(feature = "perf_ui"))]
+            warn!("Built without the `perf_ui` feature");
+        }
+
+        if dev_tools.is_physics_debug_enabled {
+            #[cfg(feature = "physics_debug")]
+            app.add_plugins(PhysicsDebugPlugin::default());
+            #[cfg(not(feature = "physics_debug"))]
+            warn!("Built without the `physics_debug` feature");
+        }
+    }
+}
+
+#[cfg(feature = "perf_ui")]
+fn spawn_perf_ui(mut commands: Commands) {
+    commands.spawn(PerfUiAllEntries::default());
+}
+
+fn on_results(
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    action_state: Res<ActionState<Action>>,
+    mut ew_change: EventWriter<level::ChangeEvent>,
+) {
+    if action_state.just_pressed(&Action::Confirm) {
+        if state.get() == &AppState::Failed {
+            ew_change.write(level::ChangeEvent::Reload);
+            next_state.set(AppState::InGame);
+        } else if state.get() == &AppState::Succeed {
+            ew_change.write(level::ChangeEvent::Next);
+            next_state.set(AppState::InGame);
+        }
+    }
+}
+
+// Starts a new try of the current level, counted like any other
+fn on_restart(
+    mut next_state: ResMut<NextState<AppState>>,
+    action_state: Res<ActionState<Action>>,
+    mut ew_change: EventWriter<level::ChangeEvent>,
+) {
+    if action_state.just_pressed(&Action::Restart) {
+        ew_change.write(level::ChangeEvent::Reload);
+        next_state.set(AppState::InGame);
+    }
+}
@@ -0,0 +1,166 @@
+use bevy::{
+    asset::{RecursiveDependencyLoadState, UntypedAssetId},
+    platform::collections::HashSet,
+    prelude::*,
+};
+
+use crate::{
+    AppState,
+    assets_loader::{SceneAssets, SceneAssetsState},
+    player::RocketTuningHandle,
+};
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Setup), setup)
+            .add_systems(
+                Update,
+                on_update.run_if(in_state(AppState::Setup)),
+            )
+            .add_systems(OnExit(AppState::Setup), despawn);
+    }
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct ProgressBar;
+
+#[derive(Component)]
+struct ProgressText;
+
+fn setup(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            Name::new("Loading screen"),
+            LoadingScreen,
+        ))
+        .with_children(|screen| {
+            screen
+                .spawn((
+                    Node {
+                        width: Val::Px(400.0),
+                        height: Val::Px(16.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(
+                        0.25, 0.23, 0.23,
+                    )),
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(
+                            0.9, 0.55, 0.2,
+                        )),
+                        ProgressBar,
+                    ));
+                });
+
+            screen.spawn((
+                Text::new("Loading..."),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextLayout::new_with_justify(
+                    JustifyText::Center,
+                ),
+                ProgressText,
+            ));
+        });
+}
+
+fn on_update(
+    asset_server: Res<AssetServer>,
+    scene_assets: Res<SceneAssets>,
+    rocket_tuning_handle: Res<RocketTuningHandle>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut reported_failures: Local<HashSet<UntypedAssetId>>,
+    mut progress_bar: Single<&mut Node, With<ProgressBar>>,
+    mut progress_text: Single<&mut Text, With<ProgressText>>,
+) {
+    let mut handles = scene_assets.untyped_handles();
+    handles.push(rocket_tuning_handle.0.clone().untyped());
+    let mut loaded_count = 0;
+    let mut failed_paths = Vec::new();
+
+    for handle in handles.iter() {
+        match asset_server
+            .get_recursive_dependency_load_state(handle.id())
+        {
+            Some(RecursiveDependencyLoadState::Loaded) => {
+                loaded_count += 1;
+            }
+            Some(RecursiveDependencyLoadState::Failed(err)) => {
+                let path = asset_server
+                    .get_path(handle.id())
+                    .map(|path| path.to_string())
+                    .unwrap_or_else(|| {
+                        format!("{:?}", handle.id())
+                    });
+
+                if reported_failures.insert(handle.id()) {
+                    error!("Failed to load asset {path}: {err}");
+                }
+                failed_paths.push(path);
+            }
+            _ => {}
+        }
+    }
+
+    if let SceneAssetsState::Failed(errors) = &scene_assets.state
+    {
+        failed_paths
+            .extend(errors.iter().map(|err| err.to_string()));
+    }
+
+    let progress = if handles.is_empty() {
+        1.0
+    } else {
+        loaded_count as f32 / handles.len() as f32
+    };
+    progress_bar.width = Val::Percent(progress * 100.0);
+
+    if !failed_paths.is_empty() {
+        progress_text.0 = format!(
+            "Failed to load:\n{}",
+            failed_paths.join("\n")
+        );
+        return;
+    }
+
+    progress_text.0 =
+        format!("Loading... {:.0}%", progress * 100.0);
+
+    if loaded_count == handles.len()
+        && matches!(scene_assets.state, SceneAssetsState::Ready)
+    {
+        next_state.set(AppState::MainMenu);
+    }
+}
+
+fn despawn(
+    mut commands: Commands,
+    query: Query<Entity, With<LoadingScreen>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -1,134 +1,99 @@
-mod assets_loader;
-mod collision;
-mod level;
-mod player;
-mod sounds;
-
-use bevy::{prelude::*, window::PresentMode};
-use bevy_inspector_egui::{
-    bevy_egui::EguiPlugin, quick::WorldInspectorPlugin,
+#[cfg(feature = "desktop")]
+use bevy::{
+    prelude::*,
+    window::{WindowMode, WindowResolution},
 };
-use iyes_perf_ui::prelude::*;
-
-use crate::{
-    collision::CollisionPlugin, level::LevelPlugin,
-    player::PlayerPlugin, sounds::SoundsPlugin,
+#[cfg(feature = "desktop")]
+use boost::{BoostGamePlugin, DevTools, save::SavePath};
+use boost::{
+    options::{self, LaunchOptions, OptionsError},
+    simulation::{Simulation, SimulationOutcome},
 };
-use assets_loader::AssetLoaderPlugin;
-
-pub const IN_DEVELOPMENT: bool = true;
-pub const IS_PERFOMANCE_PANEL_ENABLED: bool = false;
-
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Default, States)]
-pub enum AppState {
-    #[default]
-    Setup,
-    InGame,
-    Failed,
-    Succeed,
-}
 
 fn main() {
-    let mut app = App::new();
+    let options = match LaunchOptions::from_env() {
+        Ok(options) => options,
+        Err(OptionsError::Help) => {
+            println!("{}", options::USAGE);
+            return;
+        }
+        Err(err) => {
+            eprintln!("{err}\n\n{}", options::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if options.is_headless {
+        run_headless(options);
+    } else {
+        run_windowed(options);
+    }
+}
 
-    app.add_plugins(DefaultPlugins.set(WindowPlugin {
-        primary_window: Some(Window {
-            // Turn off vsync to maximize CPU/GPU usage
-            present_mode: PresentMode::AutoNoVsync,
-            ..default()
-        }),
-        ..default()
-    }))
-    .insert_resource(ClearColor(Color::srgb(0.133, 0.12, 0.12)))
-    .init_state::<AppState>()
-    .insert_resource(AmbientLight {
-        brightness: 400.0,
+#[cfg(feature = "desktop")]
+fn run_windowed(options: LaunchOptions) {
+    let mut game = BoostGamePlugin {
+        dev_tools: DevTools {
+            is_inspector_enabled: options.is_inspector_enabled,
+            is_perf_ui_enabled: options.is_perf_ui_enabled,
+            is_physics_debug_enabled: options
+                .is_physics_debug_enabled,
+        },
         ..default()
-    })
-    .add_plugins((
-        AssetLoaderPlugin,
-        CollisionPlugin,
-        LevelPlugin,
-        PlayerPlugin,
-        SoundsPlugin,
-    ))
-    .add_systems(OnEnter(AppState::Setup), setup)
-    .add_systems(
-        Update,
-        start_game.run_if(in_state(AppState::Setup)),
-    )
-    .add_systems(
-        Update,
-        on_results.run_if(
-            in_state(AppState::Failed)
-                .or(in_state(AppState::Succeed)),
-        ),
-    );
-
-    if IN_DEVELOPMENT {
-        app.add_systems(Update, exit_on_esc);
+    };
+    if let Some(size) = options.window_size {
+        game.window.resolution =
+            WindowResolution::new(size.x, size.y);
+    }
+    if options.is_fullscreen {
+        game.window.mode = WindowMode::BorderlessFullscreen(
+            MonitorSelection::Current,
+        );
+    }
 
-        app.add_plugins((
-            EguiPlugin::default(),
-            WorldInspectorPlugin::default(),
-        ));
+    let mut app = App::new();
 
-        if IS_PERFOMANCE_PANEL_ENABLED {
-            app
-                .add_plugins(bevy::diagnostic::FrameTimeDiagnosticsPlugin::default())
-                .add_plugins(bevy::diagnostic::EntityCountDiagnosticsPlugin)
-                .add_plugins(bevy::diagnostic::SystemInformationDiagnosticsPlugin)
-                .add_plugins(bevy::render::diagnostic::RenderDiagnosticsPlugin)
-                .add_plugins(PerfUiPlugin);
-        }
+    if let Some(save_file) = &options.save_file {
+        app.insert_resource(SavePath(save_file.clone()));
     }
 
-    app.run();
+    app.insert_resource(options).add_plugins(game).run();
 }
 
-fn start_game(mut next_state: ResMut<NextState<AppState>>) {
-    next_state.set(AppState::InGame);
+// Without a window system or audio, only simulations can run
+#[cfg(not(feature = "desktop"))]
+fn run_windowed(_options: LaunchOptions) {
+    eprintln!(
+        "Built without the `desktop` feature, only --headless runs \
+         are available"
+    );
+    std::process::exit(2);
 }
 
-fn setup(mut commands: Commands) {
-    commands.spawn((
-        Name::new("Main camera"),
-        Camera3d::default(),
-        Projection::Perspective(PerspectiveProjection {
-            fov: 65.0f32.to_radians(),
-            ..default()
-        }),
-        Transform::from_xyz(0.0, 4.5, 9.0)
-            .looking_at(Vec3::new(0.0, 2.0, 0.0), Vec3::Y),
-    ));
-
-    if IN_DEVELOPMENT {
-        commands.spawn(PerfUiAllEntries::default());
+// Exits with a failure code unless the scripted run lands
+fn run_headless(options: LaunchOptions) {
+    let mut simulation =
+        Simulation::new(options.start_level, options.script);
+    if let Some(save_file) = options.save_file {
+        simulation = simulation.with_save_path(save_file);
     }
-}
 
-fn on_results(
-    state: Res<State<AppState>>,
-    mut next_state: ResMut<NextState<AppState>>,
-    keys: Res<ButtonInput<KeyCode>>,
-    mut ew_change: EventWriter<level::ChangeEvent>,
-) {
-    if keys.just_pressed(KeyCode::Space) {
-        if state.get() == &AppState::Failed {
-            ew_change.write(level::ChangeEvent::Reload);
-            next_state.set(AppState::InGame);
-        } else if state.get() == &AppState::Succeed {
-            ew_change.write(level::ChangeEvent::Next);
-            next_state.set(AppState::InGame);
+    let report = match simulation.run() {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(2);
         }
-    }
-}
+    };
+
+    println!(
+        "{:?}: {:?} after {} ticks ({:.2}s)",
+        report.level,
+        report.outcome,
+        report.ticks,
+        report.elapsed_secs
+    );
 
-fn exit_on_esc(
-    keys: Res<ButtonInput<KeyCode>>,
-    mut exit: EventWriter<AppExit>,
-) {
-    if keys.just_pressed(KeyCode::Escape) {
-        exit.write(AppExit::Success);
+    if report.outcome != SimulationOutcome::Succeed {
+        std::process::exit(1);
     }
 }
@@ -0,0 +1,480 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    AppState,
+    controls::{Action, Rebinding},
+    level::{ChangeEvent, LEVELS_ORDER, Level, UnlockedLevels},
+    player::WatchReplayEvent,
+    save::Settings,
+};
+
+const BUTTON_COLOR: Color = Color::srgb(0.25, 0.23, 0.23);
+const HOVERED_BUTTON_COLOR: Color = Color::srgb(0.9, 0.55, 0.2);
+const LOCKED_BUTTON_COLOR: Color = Color::srgb(0.16, 0.15, 0.15);
+const DEADZONES: [f32; 5] = [0.0, 0.05, 0.1, 0.15, 0.25];
+const RESPONSE_EXPONENTS: [f32; 4] = [1.0, 1.5, 2.0, 3.0];
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::MainMenu),
+            setup_main_menu,
+        )
+        .add_systems(
+            OnEnter(AppState::LevelSelect),
+            setup_level_select,
+        )
+        .add_systems(OnEnter(AppState::Paused), setup_pause_menu)
+        .add_systems(
+            OnEnter(AppState::Controls),
+            setup_controls_menu,
+        )
+        .add_systems(OnExit(AppState::MainMenu), despawn)
+        .add_systems(OnExit(AppState::LevelSelect), despawn)
+        .add_systems(OnExit(AppState::Paused), despawn)
+        .add_systems(
+            OnExit(AppState::Controls),
+            (despawn, cancel_rebinding),
+        )
+        .add_systems(
+            Update,
+            (
+                on_button_pressed,
+                on_settings_button_pressed,
+                update_button_colors,
+                update_settings_labels.run_if(
+                    resource_changed::<Settings>
+                        .or(resource_changed::<Rebinding>),
+                ),
+                toggle_pause.run_if(
+                    in_state(AppState::InGame)
+                        .or(in_state(AppState::Paused)),
+                ),
+            ),
+        );
+    }
+}
+
+#[derive(Component)]
+struct MenuScreen;
+
+#[derive(Component, Debug, Clone, Copy)]
+enum MenuButton {
+    Play,
+    LevelSelect,
+    WatchReplay,
+    ToggleGhost,
+    Controls,
+    Rebind(Action),
+    CycleDeadzone,
+    CycleResponseCurve,
+    SelectLevel(Level),
+    Resume,
+    MainMenu,
+    Quit,
+}
+
+#[derive(Component)]
+struct Locked;
+
+// Labels of the buttons showing a setting, `None` for the others
+fn settings_label(
+    menu_button: MenuButton,
+    settings: &Settings,
+    rebinding: &Rebinding,
+) -> Option<String> {
+    match menu_button {
+        MenuButton::ToggleGhost if settings.is_ghost_enabled => {
+            Some("Ghost: on".to_string())
+        }
+        MenuButton::ToggleGhost => {
+            Some("Ghost: off".to_string())
+        }
+        MenuButton::Rebind(action)
+            if rebinding.0 == Some(action)
+                && action.is_axis() =>
+        {
+            Some(format!("{action:?}: move a stick or trigger"))
+        }
+        MenuButton::Rebind(action)
+            if rebinding.0 == Some(action) =>
+        {
+            Some(format!("{action:?}: press a key or button"))
+        }
+        MenuButton::CycleDeadzone => Some(format!(
+            "Stick deadzone: {:.0}%",
+            settings.analog.deadzone * 100.0
+        )),
+        MenuButton::CycleResponseCurve => Some(format!(
+            "Response curve: {:.1}",
+            settings.analog.response_exponent
+        )),
+        MenuButton::Rebind(action) => Some(format!(
+            "{action:?}: {}",
+            settings.bindings.describe(action)
+        )),
+        _ => None,
+    }
+}
+
+fn setup_main_menu(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+) {
+    spawn_menu(
+        &mut commands,
+        "Boost",
+        vec![
+            ("Play".to_string(), MenuButton::Play, false),
+            (
+                "Select level".to_string(),
+                MenuButton::LevelSelect,
+                false,
+            ),
+            (
+                "Watch last run".to_string(),
+                MenuButton::WatchReplay,
+                false,
+            ),
+            (
+                settings_label(
+                    MenuButton::ToggleGhost,
+                    &settings,
+                    &rebinding,
+                )
+                .unwrap_or_default(),
+                MenuButton::ToggleGhost,
+                false,
+            ),
+            (
+                "Controls".to_string(),
+                MenuButton::Controls,
+                false,
+            ),
+            ("Quit".to_string(), MenuButton::Quit, false),
+        ],
+    );
+}
+
+fn setup_level_select(
+    mut commands: Commands,
+    unlocked_levels: Res<UnlockedLevels>,
+) {
+    let mut buttons: Vec<_> = LEVELS_ORDER
+        .iter()
+        .enumerate()
+        .map(|(index, level)| {
+            let is_locked = !unlocked_levels.is_unlocked(*level);
+            let label = if is_locked {
+                format!("Level {} (locked)", index + 1)
+            } else {
+                format!("Level {}", index + 1)
+            };
+
+            (label, MenuButton::SelectLevel(*level), is_locked)
+        })
+        .collect();
+    buttons.push((
+        "Back".to_string(),
+        MenuButton::MainMenu,
+        false,
+    ));
+
+    spawn_menu(&mut commands, "Select level", buttons);
+}
+
+fn setup_controls_menu(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+) {
+    let mut buttons: Vec<_> = Action::ALL
+        .iter()
+        .map(|action| {
+            let menu_button = MenuButton::Rebind(*action);
+            let label = settings_label(
+                menu_button,
+                &settings,
+                &rebinding,
+            )
+            .unwrap_or_default();
+
+            (label, menu_button, false)
+        })
+        .collect();
+    for menu_button in [
+        MenuButton::CycleDeadzone,
+        MenuButton::CycleResponseCurve,
+    ] {
+        let label =
+            settings_label(menu_button, &settings, &rebinding)
+                .unwrap_or_default();
+
+        buttons.push((label, menu_button, false));
+    }
+    buttons.push((
+        "Back".to_string(),
+        MenuButton::MainMenu,
+        false,
+    ));
+
+    spawn_menu(&mut commands, "Controls", buttons);
+}
+
+fn setup_pause_menu(mut commands: Commands) {
+    spawn_menu(
+        &mut commands,
+        "Paused",
+        vec![
+            ("Resume".to_string(), MenuButton::Resume, false),
+            (
+                "Main menu".to_string(),
+                MenuButton::MainMenu,
+                false,
+            ),
+            ("Quit".to_string(), MenuButton::Quit, false),
+        ],
+    );
+}
+
+fn spawn_menu(
+    commands: &mut Commands,
+    title: &str,
+    buttons: Vec<(String, MenuButton, bool)>,
+) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            Name::new(format!("{title} screen")),
+            MenuScreen,
+        ))
+        .with_children(|screen| {
+            screen.spawn((
+                Text::new(title),
+                TextFont {
+                    font_size: 40.0,
+                    ..default()
+                },
+                Node {
+                    margin: UiRect::bottom(Val::Px(16.0)),
+                    ..default()
+                },
+            ));
+
+            for (label, menu_button, is_locked) in buttons {
+                let mut button = screen.spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(260.0),
+                        height: Val::Px(48.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    BackgroundColor(if is_locked {
+                        LOCKED_BUTTON_COLOR
+                    } else {
+                        BUTTON_COLOR
+                    }),
+                    menu_button,
+                ));
+                button.with_children(|button| {
+                    button.spawn((
+                        Text::new(label),
+                        TextFont {
+                            font_size: 20.0,
+                            ..default()
+                        },
+                    ));
+                });
+
+                if is_locked {
+                    button.insert(Locked);
+                }
+            }
+        });
+}
+
+fn on_button_pressed(
+    interaction_query: Query<
+        (&Interaction, &MenuButton),
+        Changed<Interaction>,
+    >,
+    unlocked_levels: Res<UnlockedLevels>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut ew_change: EventWriter<ChangeEvent>,
+    mut ew_watch_replay: EventWriter<WatchReplayEvent>,
+    mut ew_exit: EventWriter<AppExit>,
+) {
+    for (interaction, menu_button) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match menu_button {
+            MenuButton::Play => {
+                ew_change.write(ChangeEvent::Reload);
+                next_state.set(AppState::InGame);
+            }
+            MenuButton::LevelSelect => {
+                next_state.set(AppState::LevelSelect);
+            }
+            MenuButton::WatchReplay => {
+                ew_watch_replay.write(WatchReplayEvent);
+            }
+            MenuButton::Controls => {
+                next_state.set(AppState::Controls);
+            }
+            MenuButton::SelectLevel(level) => {
+                if unlocked_levels.is_unlocked(*level) {
+                    ew_change.write(ChangeEvent::Set(*level));
+                    next_state.set(AppState::InGame);
+                }
+            }
+            MenuButton::Resume => {
+                next_state.set(AppState::InGame)
+            }
+            MenuButton::MainMenu => {
+                next_state.set(AppState::MainMenu);
+            }
+            MenuButton::Quit => {
+                ew_exit.write(AppExit::Success);
+            }
+            MenuButton::ToggleGhost
+            | MenuButton::Rebind(_)
+            | MenuButton::CycleDeadzone
+            | MenuButton::CycleResponseCurve => {}
+        }
+    }
+}
+
+fn on_settings_button_pressed(
+    interaction_query: Query<
+        (&Interaction, &MenuButton),
+        Changed<Interaction>,
+    >,
+    mut settings: ResMut<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    for (interaction, menu_button) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match menu_button {
+            MenuButton::ToggleGhost => {
+                settings.is_ghost_enabled =
+                    !settings.is_ghost_enabled;
+            }
+            MenuButton::Rebind(action) => {
+                rebinding.0 = Some(*action);
+            }
+            MenuButton::CycleDeadzone => {
+                settings.analog.deadzone = next_value(
+                    &DEADZONES,
+                    settings.analog.deadzone,
+                );
+            }
+            MenuButton::CycleResponseCurve => {
+                settings.analog.response_exponent = next_value(
+                    &RESPONSE_EXPONENTS,
+                    settings.analog.response_exponent,
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+// The first value above the current one, wrapping around
+fn next_value(values: &[f32], current: f32) -> f32 {
+    values
+        .iter()
+        .copied()
+        .find(|value| *value > current + f32::EPSILON)
+        .unwrap_or(values[0])
+}
+
+type UnlockedButtonFilter =
+    (Changed<Interaction>, With<MenuButton>, Without<Locked>);
+
+fn update_button_colors(
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        UnlockedButtonFilter,
+    >,
+) {
+    for (interaction, mut background_color) in
+        button_query.iter_mut()
+    {
+        background_color.0 = match interaction {
+            Interaction::Pressed | Interaction::Hovered => {
+                HOVERED_BUTTON_COLOR
+            }
+            Interaction::None => BUTTON_COLOR,
+        };
+    }
+}
+
+fn update_settings_labels(
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+    button_query: Query<(&MenuButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (menu_button, children) in button_query.iter() {
+        let Some(label) =
+            settings_label(*menu_button, &settings, &rebinding)
+        else {
+            continue;
+        };
+
+        for child in children {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.0 = label.clone();
+            }
+        }
+    }
+}
+
+fn cancel_rebinding(mut rebinding: ResMut<Rebinding>) {
+    rebinding.0 = None;
+}
+
+fn toggle_pause(
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    action_state: Res<ActionState<Action>>,
+) {
+    if !action_state.just_pressed(&Action::Pause) {
+        return;
+    }
+
+    match state.get() {
+        AppState::InGame => next_state.set(AppState::Paused),
+        AppState::Paused => next_state.set(AppState::InGame),
+        _ => {}
+    }
+}
+
+fn despawn(
+    mut commands: Commands,
+    query: Query<Entity, With<MenuScreen>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,406 @@
+use std::{env, path::PathBuf};
+
+use bevy::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    level::{LEVELS_ORDER, Level},
+    simulation::{InputScript, InputScriptError},
+};
+
+const ENV_PREFIX: &str = "BOOST_";
+
+pub const USAGE: &str = "\
+Usage: boost [OPTIONS]
+
+Options:
+  --level <LEVEL>        Level to start on, a name or 1-4
+  --inspector            Show the world inspector
+  --no-inspector         Hide the world inspector
+  --perf-ui              Show the performance panel
+  --physics-debug        Draw collider wireframes
+  --window-size <WxH>    Window size in logical pixels
+  --fullscreen           Borderless fullscreen window
+  --save-file <PATH>     Save file to read settings and progress from,
+                         headless runs write their replay next to it
+  --headless             Simulate the start level without a window
+  --script <STEPS>       Inputs of the simulation, e.g. boost:90,none:60
+  -h, --help             Print this help
+
+Switches also take a value, e.g. --fullscreen=false. Every option can
+also be set with an environment variable, e.g. BOOST_LEVEL=3,
+BOOST_FULLSCREEN=1 or BOOST_SAVE_FILE=test.ron";
+
+// Launch options from the command line, falling back to `BOOST_*`
+// environment variables
+#[derive(Resource, Debug, Clone)]
+pub struct LaunchOptions {
+    pub start_level: Level,
+    pub is_inspector_enabled: bool,
+    pub is_perf_ui_enabled: bool,
+    pub is_physics_debug_enabled: bool,
+    pub window_size: Option<Vec2>,
+    pub is_fullscreen: bool,
+    pub save_file: Option<PathBuf>,
+    pub is_headless: bool,
+    pub script: InputScript,
+}
+
+impl Default for LaunchOptions {
+    fn default() -> Self {
+        Self {
+            start_level: Level::First,
+            is_inspector_enabled: cfg!(feature = "inspector"),
+            is_perf_ui_enabled: false,
+            is_physics_debug_enabled: false,
+            window_size: None,
+            is_fullscreen: false,
+            save_file: None,
+            is_headless: false,
+            script: InputScript::default(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum OptionsError {
+    #[error("Unknown option: {0}")]
+    UnknownOption(String),
+    #[error("Missing value for {0}")]
+    MissingValue(String),
+    #[error("Unknown level: {0}")]
+    InvalidLevel(String),
+    #[error("Invalid window size, expected WIDTHxHEIGHT: {0}")]
+    InvalidWindowSize(String),
+    #[error("Invalid value for {name}: {value}")]
+    InvalidFlag { name: String, value: String },
+    #[error("Invalid script: {0}")]
+    InvalidScript(#[from] InputScriptError),
+    #[error("{USAGE}")]
+    Help,
+}
+
+impl LaunchOptions {
+    pub fn from_env() -> Result<Self, OptionsError> {
+        let mut options = Self::default();
+
+        options.apply_env_vars()?;
+        options.apply_args(env::args().skip(1))?;
+
+        Ok(options)
+    }
+
+    fn apply_env_vars(&mut self) -> Result<(), OptionsError> {
+        let var =
+            |name: &str| env::var(format!("{ENV_PREFIX}{name}"));
+        let flag = |name: &str, value: &str| {
+            parse_flag(&format!("{ENV_PREFIX}{name}"), value)
+        };
+
+        if let Ok(value) = var("LEVEL") {
+            self.start_level = parse_level(&value)?;
+        }
+        if let Ok(value) = var("INSPECTOR") {
+            self.is_inspector_enabled =
+                flag("INSPECTOR", &value)?;
+        }
+        if let Ok(value) = var("PERF_UI") {
+            self.is_perf_ui_enabled = flag("PERF_UI", &value)?;
+        }
+        if let Ok(value) = var("PHYSICS_DEBUG") {
+            self.is_physics_debug_enabled =
+                flag("PHYSICS_DEBUG", &value)?;
+        }
+        if let Ok(value) = var("WINDOW_SIZE") {
+            self.window_size = Some(parse_window_size(&value)?);
+        }
+        if let Ok(value) = var("FULLSCREEN") {
+            self.is_fullscreen = flag("FULLSCREEN", &value)?;
+        }
+        if let Ok(value) = var("SAVE_FILE") {
+            self.save_file = Some(PathBuf::from(value));
+        }
+        if let Ok(value) = var("HEADLESS") {
+            self.is_headless = flag("HEADLESS", &value)?;
+        }
+        if let Ok(value) = var("SCRIPT") {
+            self.script = value.parse()?;
+        }
+
+        Ok(())
+    }
+
+    fn apply_args(
+        &mut self,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<(), OptionsError> {
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            // Both `--level 2` and `--level=2` are accepted
+            let (name, inline_value) = match arg.split_once('=')
+            {
+                Some((name, value)) => {
+                    (name.to_string(), Some(value.to_string()))
+                }
+                None => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| {
+                        OptionsError::MissingValue(name.clone())
+                    })
+            };
+            // Switches never take the next argument, only an inline
+            // value like `--fullscreen=false`
+            let flag = || match &inline_value {
+                Some(value) => parse_flag(&name, value),
+                None => Ok(true),
+            };
+
+            match name.as_str() {
+                "--level" => {
+                    self.start_level = parse_level(&value()?)?;
+                }
+                "--inspector" => {
+                    self.is_inspector_enabled = flag()?;
+                }
+                "--no-inspector" => {
+                    self.is_inspector_enabled = !flag()?;
+                }
+                "--perf-ui" => self.is_perf_ui_enabled = flag()?,
+                "--physics-debug" => {
+                    self.is_physics_debug_enabled = flag()?;
+                }
+                "--window-size" => {
+                    self.window_size =
+                        Some(parse_window_size(&value()?)?);
+                }
+                "--fullscreen" => self.is_fullscreen = flag()?,
+                "--save-file" => {
+                    self.save_file =
+                        Some(PathBuf::from(value()?));
+                }
+                "--headless" => self.is_headless = flag()?,
+                "--script" => self.script = value()?.parse()?,
+                "-h" | "--help" => {
+                    return Err(OptionsError::Help);
+                }
+                _ => {
+                    return Err(OptionsError::UnknownOption(
+                        name,
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// A level name like `second` or its 1-based number
+fn parse_level(value: &str) -> Result<Level, OptionsError> {
+    let invalid =
+        || OptionsError::InvalidLevel(value.to_string());
+
+    if let Ok(number) = value.parse::<usize>() {
+        return number
+            .checked_sub(1)
+            .and_then(|index| LEVELS_ORDER.get(index))
+            .copied()
+            .ok_or_else(invalid);
+    }
+
+    LEVELS_ORDER
+        .iter()
+        .find(|level| {
+            format!("{level:?}").eq_ignore_ascii_case(value)
+        })
+        .copied()
+        .ok_or_else(invalid)
+}
+
+fn parse_window_size(value: &str) -> Result<Vec2, OptionsError> {
+    let invalid =
+        || OptionsError::InvalidWindowSize(value.to_string());
+    let (width, height) =
+        value.split_once(['x', 'X']).ok_or_else(invalid)?;
+    let width: f32 =
+        width.trim().parse().map_err(|_| invalid())?;
+    let height: f32 =
+        height.trim().parse().map_err(|_| invalid())?;
+
+    if width <= 0.0 || height <= 0.0 {
+        return Err(invalid());
+    }
+
+    Ok(Vec2::new(width, height))
+}
+
+fn parse_flag(
+    name: &str,
+    value: &str,
+) -> Result<bool, OptionsError> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" | "" => Ok(false),
+        _ => Err(OptionsError::InvalidFlag {
+            name: name.to_string(),
+            value: value.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controls::Action;
+
+    fn parse(
+        args: &[&str],
+    ) -> Result<LaunchOptions, OptionsError> {
+        let mut options = LaunchOptions::default();
+        options.apply_args(
+            args.iter().map(|arg| arg.to_string()),
+        )?;
+
+        Ok(options)
+    }
+
+    #[test]
+    fn level_by_number_or_name() {
+        let options = parse(&["--level", "2"]).unwrap();
+        assert_eq!(options.start_level, Level::Second);
+
+        let options = parse(&["--level=Fourth"]).unwrap();
+        assert_eq!(options.start_level, Level::Fourth);
+    }
+
+    #[test]
+    fn unknown_level_is_rejected() {
+        for value in ["0", "5", "fifth", ""] {
+            assert!(matches!(
+                parse(&["--level", value]),
+                Err(OptionsError::InvalidLevel(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn missing_value_is_rejected() {
+        assert!(matches!(
+            parse(&["--level"]),
+            Err(OptionsError::MissingValue(name)) if name == "--level"
+        ));
+    }
+
+    #[test]
+    fn window_size() {
+        let options =
+            parse(&["--window-size", "1280x720"]).unwrap();
+        assert_eq!(
+            options.window_size,
+            Some(Vec2::new(1280.0, 720.0))
+        );
+
+        let options = parse(&["--window-size=800X600"]).unwrap();
+        assert_eq!(
+            options.window_size,
+            Some(Vec2::new(800.0, 600.0))
+        );
+    }
+
+    #[test]
+    fn invalid_window_size_is_rejected() {
+        for value in
+            ["1280", "1280x", "wide x tall", "0x720", "-1x1"]
+        {
+            assert!(matches!(
+                parse(&["--window-size", value]),
+                Err(OptionsError::InvalidWindowSize(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn script() {
+        let options = parse(&[
+            "--script",
+            "boost:90, boost+rotateleft:20,none:60",
+        ])
+        .unwrap();
+        let expected = InputScript::default()
+            .hold(&[Action::Boost], 90)
+            .hold(&[Action::Boost, Action::RotateLeft], 20)
+            .hold(&[], 60);
+
+        assert_eq!(
+            format!("{:?}", options.script),
+            format!("{expected:?}")
+        );
+    }
+
+    #[test]
+    fn invalid_script_is_rejected() {
+        assert!(matches!(
+            parse(&["--script", "boost"]),
+            Err(OptionsError::InvalidScript(
+                InputScriptError::InvalidStep(_)
+            ))
+        ));
+        assert!(matches!(
+            parse(&["--script", "boost:many"]),
+            Err(OptionsError::InvalidScript(
+                InputScriptError::InvalidStep(_)
+            ))
+        ));
+        assert!(matches!(
+            parse(&["--script", "fly:10"]),
+            Err(OptionsError::InvalidScript(
+                InputScriptError::UnknownAction(action)
+            )) if action == "fly"
+        ));
+    }
+
+    #[test]
+    fn switches_take_inline_values() {
+        let options = parse(&["--fullscreen"]).unwrap();
+        assert!(options.is_fullscreen);
+
+        let options = parse(&["--fullscreen=false"]).unwrap();
+        assert!(!options.is_fullscreen);
+
+        let options =
+            parse(&["--physics-debug=1", "--no-inspector=no"])
+                .unwrap();
+        assert!(options.is_physics_debug_enabled);
+        assert!(options.is_inspector_enabled);
+    }
+
+    #[test]
+    fn invalid_switch_value_is_rejected() {
+        assert!(matches!(
+            parse(&["--fullscreen=maybe"]),
+            Err(OptionsError::InvalidFlag { name, value })
+                if name == "--fullscreen" && value == "maybe"
+        ));
+    }
+
+    #[test]
+    fn switch_leaves_next_argument_alone() {
+        assert!(matches!(
+            parse(&["--fullscreen", "false"]),
+            Err(OptionsError::UnknownOption(name)) if name == "false"
+        ));
+    }
+
+    #[test]
+    fn unknown_option_is_rejected() {
+        assert!(matches!(
+            parse(&["--fly"]),
+            Err(OptionsError::UnknownOption(name)) if name == "--fly"
+        ));
+    }
+}
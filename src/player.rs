@@ -1,168 +1,398 @@
+#[cfg(feature = "desktop")]
+mod engine_sound;
+mod fuel;
+mod landing;
+mod replay;
+mod tuning;
+
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     AppState,
-    assets_loader::SceneAssets,
+    assets_loader::{ROCKET_MODEL, SceneAssets},
     collision::{
         AngularVelocity, CollisionEvent, LinearVelocity,
-        RigidBody, create_collider,
+        RigidBody, create_collider, create_physics_material,
+    },
+    controls::Action,
+    level::{
+        CurrentLevel, LevelDefinition, LevelTransform, Obstacle,
+        StartEvent,
     },
-    level::{Finish, Obstacle},
-    sounds::create_sound_effect_controller,
+    save::Settings,
+};
+
+#[cfg(feature = "desktop")]
+use engine_sound::EngineSoundPlugin;
+pub use fuel::Fuel;
+use fuel::on_fuel_pickup_collision;
+pub use landing::{Landing, LandingRules};
+use landing::{
+    LandingPlugin, check_landing, on_finish_collision,
+};
+use replay::{
+    PendingReplay, ReplayPlugin, ReplayRecorder, play_replay,
+    record_replay,
 };
+pub use replay::{ReplayPlayback, WatchReplayEvent};
+use tuning::TuningPlugin;
+pub use tuning::{
+    RocketTuning, RocketTuningHandle, RocketTuningOverride,
+    RocketTuningSet,
+};
+
+// Where the rocket stands on the launch pad model, relative to the
+// transform of the pad in the level definition
+const LAUNCH_OFFSET: Vec3 = Vec3::new(-7.5, 1.7, 0.0);
 
 #[derive(Component)]
 pub struct Player;
 
-#[derive(
-    Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect,
-)]
-enum Action {
-    Boost,
-    RotateLeft,
-    RotateRight,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrashReason {
+    Obstacle,
+    HardLanding,
+    TiltedLanding,
+}
+
+#[derive(Event, Debug)]
+pub struct PlayerCrashed {
+    pub reason: CrashReason,
+    pub entity: Entity,
+    pub velocity: Vec3,
+}
+
+#[derive(Event, Debug)]
+pub struct PlayerLanded {
+    // Seconds from the player spawn to the landing
+    pub time: f32,
+    pub tries: u32,
+    pub fuel_used: f32,
+}
+
+#[derive(Component, Debug, Default)]
+pub struct RunStats {
+    pub elapsed_secs: f32,
+    is_over: bool,
+}
+
+impl RunStats {
+    // Only the first outcome of a try counts, so later collisions in the
+    // same frame must not emit another event
+    fn try_finish(&mut self) -> bool {
+        let is_first_outcome = !self.is_over;
+        self.is_over = true;
+
+        is_first_outcome
+    }
 }
 
 #[derive(Resource, Default)]
 pub struct TriesCounter(pub u32);
 
+// Thrust and rotation of the current fixed tick, quantized so a replay
+// feeds the physics exactly the same values as the recorded run
+#[derive(
+    Component,
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+struct RocketInput {
+    thrust: u8,
+    // Positive values turn counterclockwise
+    rotation: i8,
+}
+
+impl RocketInput {
+    fn new(thrust: f32, rotation: f32) -> Self {
+        Self {
+            thrust: (thrust.clamp(0.0, 1.0) * u8::MAX as f32)
+                .round() as u8,
+            rotation: (rotation.clamp(-1.0, 1.0)
+                * i8::MAX as f32)
+                .round() as i8,
+        }
+    }
+
+    fn thrust(&self) -> f32 {
+        self.thrust as f32 / u8::MAX as f32
+    }
+
+    fn rotation(&self) -> f32 {
+        self.rotation as f32 / i8::MAX as f32
+    }
+}
+
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<TriesCounter>()
+            .add_event::<PlayerCrashed>()
+            .add_event::<PlayerLanded>()
             .add_plugins((
-                InputManagerPlugin::<Action>::default(),
+                LandingPlugin,
+                ReplayPlugin,
+                TuningPlugin,
             ))
+            // Everything a try depends on is stepped with the physics
+            // ticks, so replays play out exactly like the recorded run
             .add_systems(
-                OnEnter(AppState::InGame),
-                increment_tries_counter,
+                FixedUpdate,
+                (
+                    on_obstacle_collision,
+                    on_finish_collision,
+                    on_fuel_pickup_collision,
+                    read_input,
+                    play_replay,
+                    record_replay,
+                    on_fixed_update,
+                    tick_run_stats,
+                    check_landing,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
             )
             .add_systems(
                 Update,
-                (on_update, on_obstacle_or_finish_collision)
+                on_run_outcome
                     .run_if(in_state(AppState::InGame)),
             )
+            // Resuming from the pause menu also enters `InGame`, so a new
+            // try starts from the level events instead. Spawned on a
+            // fixed tick like the level, so both start on the same one
             .add_systems(
-                OnEnter(AppState::InGame),
-                (despawn, setup).chain(),
+                FixedPreUpdate,
+                (despawn, setup, increment_tries_counter)
+                    .chain()
+                    .after(RocketTuningSet)
+                    .run_if(on_event::<StartEvent>)
+                    .run_if(not(in_state(AppState::Setup))),
             );
+
+        #[cfg(feature = "desktop")]
+        app.add_plugins(EngineSoundPlugin);
     }
 }
 
-fn on_update(
+// The player at the controls, a replay feeds its own inputs
+type LivePlayer = (With<Player>, Without<ReplayPlayback>);
+
+fn read_input(
+    mut player: Single<
+        (&ActionState<Action>, &mut RocketInput),
+        LivePlayer,
+    >,
+    settings: Res<Settings>,
+) {
+    let (action_state, input) = &mut *player;
+    let analog = &settings.analog;
+
+    // Keys and trigger buttons report their value through `Boost`
+    let thrust = action_state
+        .button_value(&Action::Boost)
+        .max(action_state.value(&Action::Thrust));
+    // Pushing the stick right turns clockwise, a tilted stick wins
+    // over the full turn of the keys and buttons
+    let stick_rotation =
+        analog.apply(-action_state.value(&Action::Rotate));
+    let rotation = if stick_rotation != 0.0 {
+        stick_rotation
+    } else if action_state.pressed(&Action::RotateLeft) {
+        1.0
+    } else if action_state.pressed(&Action::RotateRight) {
+        -1.0
+    } else {
+        0.0
+    };
+
+    **input = RocketInput::new(analog.apply(thrust), rotation);
+}
+
+fn on_fixed_update(
     mut player: Single<
         (
             &mut Transform,
             &mut LinearVelocity,
             &mut AngularVelocity,
-            &ActionState<Action>,
+            &mut Fuel,
+            &RocketInput,
         ),
         With<Player>,
     >,
-    // TODO: Make Crashed and Landed events and use them to mutate game state
-    // TODO: Implement using events, fix bug when sound continues playing after end if boost action wasn't released on game end
-    sound_controller: Single<&AudioSink, With<Player>>,
+    rocket_tuning: Res<RocketTuning>,
     time: Res<Time>,
 ) {
     let (
         transform,
         linear_velocity,
         angular_velocity,
-        action_state,
+        fuel,
+        input,
     ) = &mut *player;
+    let thrust = input.thrust();
 
-    if action_state.pressed(&Action::Boost) {
+    if thrust > 0.0 && !fuel.is_empty() {
         let top = transform.rotation * Vec3::Y;
-        let multiplier = time.delta_secs() * 15.0;
+        let multiplier =
+            time.delta_secs() * rocket_tuning.thrust * thrust;
 
         linear_velocity.x += top.x * multiplier;
         linear_velocity.y += top.y * multiplier;
-
-        sound_controller.play();
-    } else {
-        sound_controller.pause();
+        fuel.drain(time.delta_secs() * thrust);
     }
 
-    if action_state.pressed(&Action::RotateLeft) {
-        angular_velocity.z += time.delta_secs() * 5.0;
-    } else if action_state.pressed(&Action::RotateRight) {
-        angular_velocity.z -= time.delta_secs() * 5.0;
+    angular_velocity.z += time.delta_secs()
+        * rocket_tuning.rotation_torque
+        * input.rotation();
+}
+
+fn tick_run_stats(
+    mut run_stats: Single<&mut RunStats, With<Player>>,
+    time: Res<Time>,
+) {
+    if !run_stats.is_over {
+        run_stats.elapsed_secs += time.delta_secs();
     }
 }
 
-// TODO: Split logics into several systems
-fn on_obstacle_or_finish_collision(
+fn on_obstacle_collision(
     mut er_collision: EventReader<CollisionEvent>,
-    tries_counter: Res<TriesCounter>,
-    mut was_triggered_on_this_run: Local<bool>,
-    mut current_try: Local<u32>,
-    mut next_state: ResMut<NextState<AppState>>,
-    player_query: Query<(), With<Player>>,
+    mut ew_crashed: EventWriter<PlayerCrashed>,
+    mut player: Single<
+        (Entity, &LinearVelocity, &mut RunStats),
+        With<Player>,
+    >,
     obstacle_query: Query<(), With<Obstacle>>,
-    finish_query: Query<(), With<Finish>>,
 ) {
-    if *current_try != tries_counter.0 {
-        *was_triggered_on_this_run = false;
-        *current_try = tries_counter.0;
-    }
+    let (player_entity, linear_velocity, run_stats) =
+        &mut *player;
 
     for ev in er_collision.read() {
-        if *was_triggered_on_this_run {
-            continue;
-        }
-
-        let is_player_and_obstacle_collided =
-            player_query.get(ev.0).is_ok()
-                && obstacle_query.get(ev.1).is_ok()
-                || player_query.get(ev.1).is_ok()
-                    && obstacle_query.get(ev.0).is_ok();
-        let is_player_and_finish_collided =
-            player_query.get(ev.0).is_ok()
-                && finish_query.get(ev.1).is_ok()
-                || player_query.get(ev.1).is_ok()
-                    && finish_query.get(ev.0).is_ok();
-
-        if is_player_and_obstacle_collided {
-            next_state.set(AppState::Failed);
-        }
-        if is_player_and_finish_collided {
-            next_state.set(AppState::Succeed);
-        }
+        let is_player_and_obstacle_collided = ev.0
+            == *player_entity
+            && obstacle_query.get(ev.1).is_ok()
+            || ev.1 == *player_entity
+                && obstacle_query.get(ev.0).is_ok();
 
         if is_player_and_obstacle_collided
-            || is_player_and_finish_collided
+            && run_stats.try_finish()
         {
-            *was_triggered_on_this_run = true;
+            ew_crashed.write(PlayerCrashed {
+                reason: CrashReason::Obstacle,
+                entity: *player_entity,
+                velocity: linear_velocity.0,
+            });
         }
     }
 }
 
+fn on_run_outcome(
+    mut er_crashed: EventReader<PlayerCrashed>,
+    mut er_landed: EventReader<PlayerLanded>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for ev in er_crashed.read() {
+        info!("Player crashed: {:?}", ev.reason);
+        next_state.set(AppState::Failed);
+    }
+    for ev in er_landed.read() {
+        info!("Player landed in {:.2}s", ev.time);
+        next_state.set(AppState::Succeed);
+    }
+}
+
 fn setup(
     mut commands: Commands,
     scene_assets: Res<SceneAssets>,
+    current_level: Res<CurrentLevel>,
+    level_definitions: Res<Assets<LevelDefinition>>,
+    rocket_tuning: Res<RocketTuning>,
+    pending_replay: Option<Res<PendingReplay>>,
+    settings: Res<Settings>,
 ) {
-    let collider = scene_assets.rocket.collider.clone().unwrap();
+    let Some(rocket) = scene_assets.get_game_asset(ROCKET_MODEL)
+    else {
+        error!(
+            "Rocket model is missing, can't spawn the player"
+        );
+        return;
+    };
+    let Some(collider) = rocket.collider.clone() else {
+        error!(
+            "Rocket collider is missing, can't spawn the player"
+        );
+        return;
+    };
+    let Some(definition) = current_level
+        .definition(&scene_assets, &level_definitions)
+    else {
+        error!(
+            "Level definition is missing, can't spawn the player"
+        );
+        return;
+    };
 
-    commands.spawn((
-        SceneRoot(scene_assets.rocket.model.clone()),
-        Transform::from_xyz(-7.5, 1.7, 0.0),
+    let mut player = commands.spawn((
+        SceneRoot(rocket.model.clone()),
+        launch_transform(&definition.launch_pad.transform),
         create_collider(RigidBody::Dynamic, collider),
-        InputMap::new([
-            (Action::Boost, KeyCode::Space),
-            (Action::Boost, KeyCode::KeyW),
-            (Action::RotateLeft, KeyCode::KeyA),
-            (Action::RotateLeft, KeyCode::ArrowLeft),
-            (Action::RotateRight, KeyCode::KeyD),
-            (Action::RotateRight, KeyCode::ArrowRight),
-        ]),
-        create_sound_effect_controller(
-            scene_assets.main_engine_thrust_sound.clone(),
-        ),
+        create_physics_material(&rocket_tuning),
+        ActionState::<Action>::default(),
+        RocketInput::default(),
+        Fuel::new(definition.starting_fuel),
+        Landing::default(),
+        RunStats::default(),
         Name::new("Player"),
         Player,
     ));
+
+    // A replayed rocket ignores the keyboard and follows the recording
+    match &pending_replay {
+        Some(pending_replay) => {
+            player.insert(ReplayPlayback::new(
+                pending_replay.0.clone(),
+            ));
+        }
+        None => {
+            player.insert((
+                settings.bindings.input_map(),
+                ReplayRecorder::new(current_level.level()),
+            ));
+        }
+    }
+
+    if pending_replay.is_some() {
+        commands.remove_resource::<PendingReplay>();
+    }
+}
+
+// Upright on the pad, turned and moved with it but never scaled
+fn launch_transform(launch_pad: &LevelTransform) -> Transform {
+    let launch_pad = Transform::from(launch_pad);
+
+    Transform::from_translation(
+        launch_pad.transform_point(LAUNCH_OFFSET),
+    )
+    .with_rotation(launch_pad.rotation)
+}
+
+// Nothing a replay does is kept, like unlocks or records
+pub fn is_replaying(
+    replay_query: Query<
+        (),
+        (With<Player>, With<ReplayPlayback>),
+    >,
+) -> bool {
+    !replay_query.is_empty()
 }
 
 fn despawn(
@@ -172,6 +402,7 @@ fn despawn(
     commands.entity(*player).despawn();
 }
 
+// Tries are counted per level, switching to another one starts over
 fn increment_tries_counter(
     mut tries_counter: ResMut<TriesCounter>,
 ) {
@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+
+use crate::{
+    AppState,
+    assets_loader::{MAIN_ENGINE_THRUST_SOUND, SceneAssets},
+    player::{Fuel, Player, RocketInput, RunStats},
+    sounds::create_sound_effect_controller,
+};
+
+pub struct EngineSoundPlugin;
+
+impl Plugin for EngineSoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (add_engine_sound, update_engine_sound).chain(),
+        );
+    }
+}
+
+fn add_engine_sound(
+    mut commands: Commands,
+    scene_assets: Res<SceneAssets>,
+    player_query: Query<Entity, Added<Player>>,
+) {
+    for entity in player_query.iter() {
+        let Some(sound) =
+            scene_assets.get_sound(MAIN_ENGINE_THRUST_SOUND)
+        else {
+            warn!("Unknown sound: {MAIN_ENGINE_THRUST_SOUND}");
+            continue;
+        };
+
+        commands.entity(entity).insert(
+            create_sound_effect_controller(sound.clone()),
+        );
+    }
+}
+
+// Plays while the rocket thrusts, the sink only exists once the sound
+// started playing. Paused in the menus and once the try is over
+fn update_engine_sound(
+    player: Single<
+        (&RocketInput, &Fuel, &RunStats, &AudioSink),
+        With<Player>,
+    >,
+    state: Res<State<AppState>>,
+) {
+    let (input, fuel, run_stats, sound_controller) = *player;
+    let is_thrusting = *state.get() == AppState::InGame
+        && !run_stats.is_over
+        && input.thrust() > 0.0
+        && !fuel.is_empty();
+
+    if is_thrusting {
+        sound_controller.play();
+    } else {
+        sound_controller.pause();
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision::CollisionEvent, level::FuelPickup, player::Player,
+};
+
+const FUEL_DRAIN_PER_SECOND: f32 = 20.0;
+
+#[derive(Component, Debug)]
+pub struct Fuel {
+    pub current: f32,
+    pub max: f32,
+    pub used: f32,
+}
+
+impl Fuel {
+    pub fn new(amount: f32) -> Self {
+        Self {
+            current: amount,
+            max: amount,
+            used: 0.0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    pub fn drain(&mut self, delta_secs: f32) {
+        let drained = (FUEL_DRAIN_PER_SECOND * delta_secs)
+            .min(self.current);
+
+        self.current -= drained;
+        self.used += drained;
+    }
+
+    pub fn refill(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+}
+
+pub(super) fn on_fuel_pickup_collision(
+    mut commands: Commands,
+    mut er_collision: EventReader<CollisionEvent>,
+    mut player: Single<(Entity, &mut Fuel), With<Player>>,
+    pickup_query: Query<&FuelPickup>,
+) {
+    let (player_entity, fuel) = &mut *player;
+
+    for ev in er_collision.read() {
+        let pickup_entity = if ev.0 == *player_entity {
+            ev.1
+        } else if ev.1 == *player_entity {
+            ev.0
+        } else {
+            continue;
+        };
+
+        let Ok(pickup) = pickup_query.get(pickup_entity) else {
+            continue;
+        };
+
+        fuel.refill(pickup.amount);
+        commands.entity(pickup_entity).despawn();
+    }
+}
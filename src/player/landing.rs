@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision::{
+        CollisionEndedEvent, CollisionEvent, LinearVelocity,
+    },
+    level::Finish,
+    player::{
+        CrashReason, Fuel, Player, PlayerCrashed, PlayerLanded,
+        RunStats, TriesCounter,
+    },
+};
+
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct LandingRules {
+    // Speed right before touching the pad
+    pub max_impact_speed: f32,
+    // Speed below which the rocket counts as resting on the pad
+    pub max_settled_speed: f32,
+    pub max_tilt_degrees: f32,
+    pub dwell_secs: f32,
+}
+
+impl Default for LandingRules {
+    fn default() -> Self {
+        Self {
+            max_impact_speed: 3.0,
+            max_settled_speed: 0.5,
+            max_tilt_degrees: 15.0,
+            dwell_secs: 1.0,
+        }
+    }
+}
+
+#[derive(Component, Debug, Default)]
+pub struct Landing {
+    pub is_on_pad: bool,
+    pub settled_secs: f32,
+    // Slowly decaying maximum of the speed, as the velocity is already
+    // damped by the contact when the collision event is handled
+    recent_peak_speed: f32,
+}
+
+// Per second, roughly the gravity so the peak keeps up with free fall
+const PEAK_SPEED_DECAY: f32 = 10.0;
+
+pub struct LandingPlugin;
+
+impl Plugin for LandingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LandingRules>()
+            .register_type::<LandingRules>();
+    }
+}
+
+fn tilt_degrees(transform: &Transform) -> f32 {
+    (transform.rotation * Vec3::Y)
+        .angle_between(Vec3::Y)
+        .to_degrees()
+}
+
+pub(super) fn on_finish_collision(
+    mut er_collision: EventReader<CollisionEvent>,
+    mut er_collision_ended: EventReader<CollisionEndedEvent>,
+    mut ew_crashed: EventWriter<PlayerCrashed>,
+    mut player: Single<
+        (
+            Entity,
+            &Transform,
+            &LinearVelocity,
+            &mut Landing,
+            &mut RunStats,
+        ),
+        With<Player>,
+    >,
+    landing_rules: Res<LandingRules>,
+    finish_query: Query<(), With<Finish>>,
+) {
+    let (
+        player_entity,
+        transform,
+        linear_velocity,
+        landing,
+        run_stats,
+    ) = &mut *player;
+    let is_player_and_finish = |a: Entity, b: Entity| {
+        a == *player_entity && finish_query.get(b).is_ok()
+            || b == *player_entity && finish_query.get(a).is_ok()
+    };
+
+    for ev in er_collision_ended.read() {
+        if is_player_and_finish(ev.0, ev.1) {
+            landing.is_on_pad = false;
+            landing.settled_secs = 0.0;
+        }
+    }
+
+    for ev in er_collision.read() {
+        if !is_player_and_finish(ev.0, ev.1) {
+            continue;
+        }
+
+        let crash_reason = if landing.recent_peak_speed
+            > landing_rules.max_impact_speed
+        {
+            Some(CrashReason::HardLanding)
+        } else if tilt_degrees(transform)
+            > landing_rules.max_tilt_degrees
+        {
+            Some(CrashReason::TiltedLanding)
+        } else {
+            None
+        };
+
+        match crash_reason {
+            Some(reason) if run_stats.try_finish() => {
+                ew_crashed.write(PlayerCrashed {
+                    reason,
+                    entity: *player_entity,
+                    velocity: linear_velocity.0,
+                });
+            }
+            Some(_) => {}
+            None => landing.is_on_pad = true,
+        }
+    }
+}
+
+pub(super) fn check_landing(
+    mut ew_landed: EventWriter<PlayerLanded>,
+    mut player: Single<
+        (
+            &Transform,
+            &LinearVelocity,
+            &Fuel,
+            &mut Landing,
+            &mut RunStats,
+        ),
+        With<Player>,
+    >,
+    landing_rules: Res<LandingRules>,
+    tries_counter: Res<TriesCounter>,
+    time: Res<Time>,
+) {
+    let (transform, linear_velocity, fuel, landing, run_stats) =
+        &mut *player;
+
+    landing.recent_peak_speed = linear_velocity.length().max(
+        landing.recent_peak_speed
+            - PEAK_SPEED_DECAY * time.delta_secs(),
+    );
+
+    if !landing.is_on_pad {
+        return;
+    }
+
+    let is_settled = linear_velocity.length()
+        <= landing_rules.max_settled_speed
+        && tilt_degrees(transform)
+            <= landing_rules.max_tilt_degrees;
+
+    if !is_settled {
+        landing.settled_secs = 0.0;
+        return;
+    }
+
+    landing.settled_secs += time.delta_secs();
+
+    if landing.settled_secs >= landing_rules.dwell_secs
+        && run_stats.try_finish()
+    {
+        ew_landed.write(PlayerLanded {
+            time: run_stats.elapsed_secs,
+            tries: tries_counter.0,
+            fuel_used: fuel.used,
+        });
+    }
+}
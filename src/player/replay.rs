@@ -0,0 +1,192 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    AppState,
+    level::{ChangeEvent, Level},
+    player::{Player, PlayerCrashed, PlayerLanded, RocketInput},
+    save::{SavePath, write_file_atomically},
+};
+
+const LAST_REPLAY_FILE_NAME: &str = "last.replay.ron";
+
+// Rocket input per fixed tick, consecutive equal ticks are merged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub level: Level,
+    frames: Vec<(RocketInput, u32)>,
+}
+
+impl Replay {
+    fn new(level: Level) -> Self {
+        Self {
+            level,
+            frames: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, input: RocketInput) {
+        match self.frames.last_mut() {
+            Some((last_input, ticks))
+                if *last_input == input =>
+            {
+                *ticks += 1;
+            }
+            _ => self.frames.push((input, 1)),
+        }
+    }
+
+    fn input_at(&self, tick: u32) -> Option<RocketInput> {
+        let mut start = 0;
+
+        for (input, ticks) in self.frames.iter() {
+            if tick < start + ticks {
+                return Some(*input);
+            }
+            start += ticks;
+        }
+
+        None
+    }
+}
+
+#[derive(Debug, Error)]
+enum ReplayError {
+    #[error("Could not access replay file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse replay file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Could not serialize replay file: {0}")]
+    Serialize(#[from] ron::Error),
+}
+
+// Read by `player::setup` to spawn a rocket driven by the replay
+#[derive(Resource)]
+pub struct PendingReplay(pub Replay);
+
+#[derive(Component)]
+pub struct ReplayRecorder(Replay);
+
+impl ReplayRecorder {
+    pub fn new(level: Level) -> Self {
+        Self(Replay::new(level))
+    }
+}
+
+#[derive(Component)]
+pub struct ReplayPlayback {
+    replay: Replay,
+    tick: u32,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, tick: 0 }
+    }
+}
+
+#[derive(Event)]
+pub struct WatchReplayEvent;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<WatchReplayEvent>().add_systems(
+            Update,
+            (
+                watch_last_replay
+                    .run_if(on_event::<WatchReplayEvent>),
+                save_last_replay.run_if(
+                    on_event::<PlayerCrashed>
+                        .or(on_event::<PlayerLanded>),
+                ),
+            ),
+        );
+    }
+}
+
+fn last_replay_path(save_path: &SavePath) -> PathBuf {
+    save_path.0.with_file_name(LAST_REPLAY_FILE_NAME)
+}
+
+fn read_replay(path: &Path) -> Result<Replay, ReplayError> {
+    let content = fs::read_to_string(path)?;
+
+    Ok(ron::from_str(&content)?)
+}
+
+fn write_replay(
+    path: &Path,
+    replay: &Replay,
+) -> Result<(), ReplayError> {
+    let content = ron::to_string(replay)?;
+
+    write_file_atomically(path, &content)?;
+
+    Ok(())
+}
+
+fn watch_last_replay(
+    mut commands: Commands,
+    mut ew_change: EventWriter<ChangeEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+    save_path: Res<SavePath>,
+) {
+    let replay = match read_replay(&last_replay_path(&save_path))
+    {
+        Ok(replay) => replay,
+        Err(err) => {
+            error!("{err}");
+            return;
+        }
+    };
+
+    ew_change.write(ChangeEvent::Set(replay.level));
+    next_state.set(AppState::InGame);
+    commands.insert_resource(PendingReplay(replay));
+}
+
+fn save_last_replay(
+    recorder: Single<&ReplayRecorder, With<Player>>,
+    save_path: Res<SavePath>,
+) {
+    if let Err(err) =
+        write_replay(&last_replay_path(&save_path), &recorder.0)
+    {
+        error!("{err}");
+    }
+}
+
+pub(super) fn play_replay(
+    mut player: Single<
+        (&mut ReplayPlayback, &mut RocketInput),
+        With<Player>,
+    >,
+) {
+    let (playback, input) = &mut *player;
+
+    // Nothing is pressed once the recording is over
+    **input = playback
+        .replay
+        .input_at(playback.tick)
+        .unwrap_or_default();
+    playback.tick += 1;
+}
+
+pub(super) fn record_replay(
+    mut player: Single<
+        (&mut ReplayRecorder, &RocketInput),
+        With<Player>,
+    >,
+) {
+    let (recorder, input) = &mut *player;
+
+    recorder.0.push(**input);
+}
@@ -0,0 +1,248 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    assets_loader::SceneAssets,
+    collision::create_physics_material,
+    level::{CurrentLevel, Level, LevelDefinition},
+    player::Player,
+};
+
+const ROCKET_TUNING_PATH: &str = "rocket.tuning.ron";
+
+// Base values come from `ROCKET_TUNING_PATH`, a level can override them
+// in its definition
+#[derive(
+    Asset,
+    Resource,
+    Reflect,
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct RocketTuning {
+    pub thrust: f32,
+    pub rotation_torque: f32,
+    pub mass: f32,
+    pub restitution: f32,
+    pub friction: f32,
+    pub collision_margin: f32,
+}
+
+impl Default for RocketTuning {
+    fn default() -> Self {
+        Self {
+            thrust: 15.0,
+            rotation_torque: 5.0,
+            mass: 100.0,
+            restitution: 0.01,
+            friction: 0.8,
+            collision_margin: 0.05,
+        }
+    }
+}
+
+// Only the values a level sets replace the ones from
+// `ROCKET_TUNING_PATH`, e.g. `(thrust: Some(20.0))`
+#[derive(
+    Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+pub struct RocketTuningOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thrust: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation_torque: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mass: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restitution: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub friction: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collision_margin: Option<f32>,
+}
+
+impl RocketTuningOverride {
+    pub fn apply(&self, base: &RocketTuning) -> RocketTuning {
+        RocketTuning {
+            thrust: self.thrust.unwrap_or(base.thrust),
+            rotation_torque: self
+                .rotation_torque
+                .unwrap_or(base.rotation_torque),
+            mass: self.mass.unwrap_or(base.mass),
+            restitution: self
+                .restitution
+                .unwrap_or(base.restitution),
+            friction: self.friction.unwrap_or(base.friction),
+            collision_margin: self
+                .collision_margin
+                .unwrap_or(base.collision_margin),
+        }
+    }
+}
+
+// Spawning a new try is ordered after this, so it starts with the
+// tuning of its level
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RocketTuningSet;
+
+// Waited for by the loading screen, so no try starts with the defaults
+#[derive(Resource)]
+pub struct RocketTuningHandle(pub Handle<RocketTuning>);
+
+#[derive(Default)]
+struct RocketTuningLoader;
+
+#[derive(Debug, Error)]
+enum RocketTuningLoaderError {
+    #[error("Could not read rocket tuning: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse rocket tuning: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for RocketTuningLoader {
+    type Asset = RocketTuning;
+    type Settings = ();
+    type Error = RocketTuningLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        Ok(ron::de::from_bytes::<RocketTuning>(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<RocketTuning>()
+            .init_asset_loader::<RocketTuningLoader>()
+            .init_resource::<RocketTuning>()
+            .register_type::<RocketTuning>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                FixedPreUpdate,
+                (apply_rocket_tuning, sync_player_physics)
+                    .chain()
+                    .in_set(RocketTuningSet),
+            );
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(RocketTuningHandle(
+        asset_server.load(ROCKET_TUNING_PATH),
+    ));
+}
+
+// The level whose override was applied last, with that override
+type AppliedLevelTuning = (
+    Level,
+    AssetId<LevelDefinition>,
+    Option<RocketTuningOverride>,
+);
+
+#[derive(SystemParam)]
+struct LevelTuning<'w> {
+    current_level: Res<'w, CurrentLevel>,
+    scene_assets: Res<'w, SceneAssets>,
+    level_definitions: Res<'w, Assets<LevelDefinition>>,
+}
+
+impl LevelTuning<'_> {
+    fn current(&self) -> AppliedLevelTuning {
+        let definition_id = self
+            .current_level
+            .definition_handle(&self.scene_assets)
+            .id();
+        let level_tuning =
+            self.level_definitions.get(definition_id).and_then(
+                |definition| definition.rocket_tuning.clone(),
+            );
+
+        (self.current_level.level(), definition_id, level_tuning)
+    }
+}
+
+// Applies the level override over the base config whenever either
+// changes, edits made in the inspector are kept until then. A new try
+// of the same level keeps them too
+fn apply_rocket_tuning(
+    mut er_rocket_tuning: EventReader<AssetEvent<RocketTuning>>,
+    mut rocket_tuning: ResMut<RocketTuning>,
+    rocket_tuning_handle: Res<RocketTuningHandle>,
+    rocket_tunings: Res<Assets<RocketTuning>>,
+    level_tuning: LevelTuning,
+    mut last_applied: Local<Option<AppliedLevelTuning>>,
+) {
+    // Drained, events left unread would apply the asset again on the
+    // next tick and drop the inspector edits
+    let is_base_tuning_changed = er_rocket_tuning
+        .read()
+        .filter(|ev| {
+            ev.is_loaded_with_dependencies(
+                &rocket_tuning_handle.0,
+            ) || ev.is_modified(&rocket_tuning_handle.0)
+        })
+        .count()
+        > 0;
+
+    let current = level_tuning.current();
+    if !is_base_tuning_changed
+        && last_applied.as_ref() == Some(&current)
+    {
+        return;
+    }
+
+    // Applied once the base config is loaded
+    let Some(base_tuning) =
+        rocket_tunings.get(&rocket_tuning_handle.0)
+    else {
+        return;
+    };
+
+    *rocket_tuning = match &current.2 {
+        Some(level_tuning) => level_tuning.apply(base_tuning),
+        None => base_tuning.clone(),
+    };
+    *last_applied = Some(current);
+}
+
+fn sync_player_physics(
+    mut commands: Commands,
+    rocket_tuning: Res<RocketTuning>,
+    player_query: Query<Entity, With<Player>>,
+) {
+    if !rocket_tuning.is_changed() {
+        return;
+    }
+
+    for entity in player_query.iter() {
+        commands
+            .entity(entity)
+            .insert(create_physics_material(&rocket_tuning));
+    }
+}
@@ -0,0 +1,311 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    AppState,
+    camera::{CameraMode, CameraRig},
+    controls::{AnalogSettings, Bindings},
+    level::{
+        CurrentLevel, Level, StartEvent, UnlockedLevels,
+        is_test_flight, unlock_next_level,
+    },
+    player::{Fuel, Player, RunStats, is_replaying},
+};
+
+const SAVE_DIR_NAME: &str = "boost";
+const SAVE_FILE_NAME: &str = "save.ron";
+pub const LEADERBOARD_SIZE: usize = 5;
+
+// Where the save file lives, insert it before `SavePlugin` to use
+// another file, e.g. in tests
+#[derive(Resource, Debug, Clone)]
+pub struct SavePath(pub PathBuf);
+
+impl Default for SavePath {
+    fn default() -> Self {
+        let dir = dirs::data_dir()
+            .map(|dir| dir.join(SAVE_DIR_NAME))
+            .unwrap_or_default();
+
+        Self(dir.join(SAVE_FILE_NAME))
+    }
+}
+
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub camera_mode: CameraMode,
+    pub is_ghost_enabled: bool,
+    pub bindings: Bindings,
+    pub analog: AnalogSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            camera_mode: CameraMode::default(),
+            is_ghost_enabled: true,
+            bindings: Bindings::default(),
+            analog: AnalogSettings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub time: f32,
+    pub tries: u32,
+    pub fuel_used: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelRecord {
+    pub best_time: Option<f32>,
+    pub fewest_tries: Option<u32>,
+    // Fastest runs first, at most `LEADERBOARD_SIZE` of them
+    pub leaderboard: Vec<LeaderboardEntry>,
+}
+
+impl LevelRecord {
+    pub fn is_leaderboard_time(&self, time: f32) -> bool {
+        self.leaderboard.len() < LEADERBOARD_SIZE
+            || self
+                .leaderboard
+                .last()
+                .is_some_and(|entry| time < entry.time)
+    }
+
+    pub fn add_to_leaderboard(
+        &mut self,
+        entry: LeaderboardEntry,
+    ) {
+        let index = self
+            .leaderboard
+            .partition_point(|other| other.time <= entry.time);
+
+        self.leaderboard.insert(index, entry);
+        self.leaderboard.truncate(LEADERBOARD_SIZE);
+    }
+}
+
+#[derive(
+    Resource, Debug, Clone, Default, Serialize, Deserialize,
+)]
+pub struct LevelRecords(pub HashMap<Level, LevelRecord>);
+
+// A run fast enough for the leaderboard, waiting for the player to type
+// in a name on the success screen
+#[derive(Resource, Debug)]
+pub struct NewLeaderboardEntry {
+    pub level: Level,
+    pub entry: LeaderboardEntry,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SaveFile {
+    unlocked_levels: UnlockedLevels,
+    records: LevelRecords,
+    settings: Settings,
+}
+
+#[derive(Debug, Error)]
+enum SaveError {
+    #[error("Could not access save file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse save file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Could not serialize save file: {0}")]
+    Serialize(#[from] ron::Error),
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SavePath>()
+            .init_resource::<Settings>()
+            .init_resource::<LevelRecords>()
+            .init_resource::<LevelTries>()
+            .add_systems(OnEnter(AppState::Setup), load)
+            .add_systems(
+                Update,
+                (
+                    apply_settings
+                        .run_if(resource_changed::<Settings>),
+                    count_level_tries
+                        .run_if(on_event::<StartEvent>),
+                    save.run_if(
+                        resource_removed::<NewLeaderboardEntry>,
+                    ),
+                    // Settings changed from the menus are kept right away
+                    save.run_if(resource_changed::<Settings>)
+                        .run_if(not(in_state(AppState::Setup))),
+                ),
+            )
+            .add_systems(
+                OnEnter(AppState::Succeed),
+                (record_run, save)
+                    .chain()
+                    .after(unlock_next_level)
+                    .run_if(not(is_replaying))
+                    .run_if(not(is_test_flight)),
+            );
+    }
+}
+
+// Tries since the current level was picked, `TriesCounter` keeps
+// counting across levels but a record only counts its own
+#[derive(Resource, Debug, Default)]
+struct LevelTries {
+    level: Option<Level>,
+    count: u32,
+}
+
+fn count_level_tries(
+    mut er_start: EventReader<StartEvent>,
+    mut level_tries: ResMut<LevelTries>,
+) {
+    for ev in er_start.read() {
+        if level_tries.level != Some(ev.0) {
+            *level_tries = LevelTries {
+                level: Some(ev.0),
+                count: 0,
+            };
+        }
+        level_tries.count += 1;
+    }
+}
+
+fn load(mut commands: Commands, save_path: Res<SavePath>) {
+    let save_file = match read_save_file(&save_path) {
+        Ok(Some(save_file)) => save_file,
+        Ok(None) => SaveFile::default(),
+        Err(err) => {
+            error!("{err}, starting with a fresh save");
+            SaveFile::default()
+        }
+    };
+
+    commands.insert_resource(save_file.unlocked_levels);
+    commands.insert_resource(save_file.records);
+    commands.insert_resource(save_file.settings);
+}
+
+fn save(
+    save_path: Res<SavePath>,
+    unlocked_levels: Res<UnlockedLevels>,
+    records: Res<LevelRecords>,
+    settings: Res<Settings>,
+) {
+    let save_file = SaveFile {
+        unlocked_levels: unlocked_levels.clone(),
+        records: records.clone(),
+        settings: settings.clone(),
+    };
+
+    if let Err(err) = write_save_file(&save_path, &save_file) {
+        error!("{err}");
+    }
+}
+
+fn read_save_file(
+    save_path: &SavePath,
+) -> Result<Option<SaveFile>, SaveError> {
+    if !save_path.0.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&save_path.0)?;
+
+    Ok(Some(ron::from_str(&content)?))
+}
+
+// Writes next to the file first, so a crash never leaves it half
+// written and nothing watching it ever reads a partial file
+pub fn write_file_atomically(
+    path: &Path,
+    content: &str,
+) -> std::io::Result<()> {
+    let mut temp_name =
+        path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&temp_path, content)?;
+    fs::rename(&temp_path, path)
+}
+
+fn write_save_file(
+    save_path: &SavePath,
+    save_file: &SaveFile,
+) -> Result<(), SaveError> {
+    let content = ron::ser::to_string_pretty(
+        save_file,
+        ron::ser::PrettyConfig::default(),
+    )?;
+
+    write_file_atomically(&save_path.0, &content)?;
+
+    Ok(())
+}
+
+fn record_run(
+    mut commands: Commands,
+    mut records: ResMut<LevelRecords>,
+    player: Single<(&RunStats, &Fuel), With<Player>>,
+    level_tries: Res<LevelTries>,
+    current_level: Res<CurrentLevel>,
+) {
+    let (run_stats, fuel) = *player;
+
+    let time = run_stats.elapsed_secs;
+    let tries = level_tries.count;
+    let level = current_level.level();
+    let record = records.0.entry(level).or_default();
+
+    if record.best_time.is_none_or(|best_time| time < best_time)
+    {
+        record.best_time = Some(time);
+    }
+    if record
+        .fewest_tries
+        .is_none_or(|fewest_tries| tries < fewest_tries)
+    {
+        record.fewest_tries = Some(tries);
+    }
+
+    if record.is_leaderboard_time(time) {
+        commands.insert_resource(NewLeaderboardEntry {
+            level,
+            entry: LeaderboardEntry {
+                name: String::new(),
+                time,
+                tries,
+                fuel_used: fuel.used,
+            },
+        });
+    }
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    rig: Option<Single<&mut CameraRig>>,
+) {
+    if let Some(mut rig) = rig {
+        rig.mode = settings.camera_mode;
+    }
+}
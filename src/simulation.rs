@@ -0,0 +1,372 @@
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "desktop")]
+use bevy::audio::AudioLoader;
+use bevy::{
+    app::PluginGroupBuilder, gltf::GltfPlugin,
+    input::InputPlugin, prelude::*, render::mesh::MeshPlugin,
+    state::app::StatesPlugin, time::TimeUpdateStrategy,
+};
+use leafwing_input_manager::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    AppState, BoostGamePlugin,
+    controls::Action,
+    level::{ChangeEvent, Level},
+    player::{CrashReason, Player, PlayerCrashed, PlayerLanded},
+    save::{SavePath, Settings},
+};
+
+const FIXED_TIMESTEP_HZ: f64 = 64.0;
+// A minute of play before the run counts as timed out
+const MAX_TICKS: u32 = 64 * 60;
+// Ticks only count once the rocket is spawned, a run that never gets
+// there gives up after this many updates
+const MAX_UPDATES: u32 = MAX_TICKS * 2;
+const LOADING_TIMEOUT: Duration = Duration::from_secs(60);
+
+// Actions held for a number of fixed ticks, one step after another
+#[derive(Debug, Clone)]
+struct InputStep {
+    actions: Vec<Action>,
+    ticks: u32,
+}
+
+#[derive(Resource, Debug, Clone, Default)]
+pub struct InputScript(Vec<InputStep>);
+
+impl InputScript {
+    pub fn hold(
+        mut self,
+        actions: &[Action],
+        ticks: u32,
+    ) -> Self {
+        self.0.push(InputStep {
+            actions: actions.to_vec(),
+            ticks,
+        });
+        self
+    }
+
+    // Nothing is held once the script is over
+    fn actions_at(&self, tick: u32) -> &[Action] {
+        let mut start = 0;
+
+        for step in self.0.iter() {
+            if tick < start + step.ticks {
+                return &step.actions;
+            }
+            start += step.ticks;
+        }
+
+        &[]
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum InputScriptError {
+    #[error("Invalid script step, expected ACTIONS:TICKS: {0}")]
+    InvalidStep(String),
+    #[error("Unknown action in script: {0}")]
+    UnknownAction(String),
+}
+
+// Steps like `boost:90,boost+rotateleft:20,none:60`
+impl FromStr for InputScript {
+    type Err = InputScriptError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut script = InputScript::default();
+
+        for step in value.split(',').map(str::trim) {
+            let invalid = || {
+                InputScriptError::InvalidStep(step.to_string())
+            };
+            let (actions, ticks) =
+                step.split_once(':').ok_or_else(invalid)?;
+            let ticks =
+                ticks.trim().parse().map_err(|_| invalid())?;
+            let actions = actions
+                .split('+')
+                .map(str::trim)
+                .filter(|name| {
+                    !name.eq_ignore_ascii_case("none")
+                })
+                .map(parse_action)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            script = script.hold(&actions, ticks);
+        }
+
+        Ok(script)
+    }
+}
+
+fn parse_action(name: &str) -> Result<Action, InputScriptError> {
+    Action::ALL
+        .into_iter()
+        .find(|action| {
+            format!("{action:?}").eq_ignore_ascii_case(name)
+        })
+        .ok_or_else(|| {
+            InputScriptError::UnknownAction(name.to_string())
+        })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimulationOutcome {
+    Succeed,
+    Failed(CrashReason),
+    TimedOut,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulationReport {
+    pub level: Level,
+    pub outcome: SimulationOutcome,
+    pub ticks: u32,
+    pub elapsed_secs: f32,
+}
+
+#[derive(Debug, Error)]
+pub enum SimulationError {
+    #[error("Assets did not finish loading in {0:?}")]
+    LoadingTimedOut(Duration),
+    #[error("The run did not finish in {0} updates")]
+    RunTimedOut(u32),
+}
+
+#[derive(Resource, Debug)]
+struct SimulationRun {
+    level: Level,
+    tick: u32,
+    outcome: Option<SimulationOutcome>,
+}
+
+// Everything `BoostGamePlugin` needs besides the game plugins when
+// running headless, time advances by exactly one fixed tick per update
+pub struct HeadlessPlugins;
+
+impl PluginGroup for HeadlessPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add_group(MinimalPlugins)
+            .add(TransformPlugin)
+            .add(InputPlugin)
+            .add(StatesPlugin)
+            .add(AssetPlugin::default())
+            .add(MeshPlugin)
+            .add(ImagePlugin::default())
+            .add(GltfPlugin::default())
+            .add(HeadlessPlugin)
+    }
+}
+
+struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        // Loaded from the asset pack but never rendered or played,
+        // without the scene plugin the models are not spawned either.
+        // The physics still looks for scenes to build colliders from
+        app.init_asset::<Scene>()
+            .init_resource::<SceneSpawner>()
+            .init_asset::<StandardMaterial>()
+            .init_asset::<AnimationClip>()
+            .insert_resource(Time::<Fixed>::from_hz(
+                FIXED_TIMESTEP_HZ,
+            ))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(
+                Duration::from_secs_f64(1.0 / FIXED_TIMESTEP_HZ),
+            ))
+            .init_state::<AppState>()
+            .init_resource::<Settings>();
+
+        // The sounds are loaded along the models, never played
+        #[cfg(feature = "desktop")]
+        app.init_asset::<AudioSource>()
+            .init_asset_loader::<AudioLoader>();
+
+        // Keeps the last replay away from the player's save
+        if !app.world().contains_resource::<SavePath>() {
+            app.insert_resource(SavePath(
+                std::env::temp_dir()
+                    .join("boost-simulation")
+                    .join("save.ron"),
+            ));
+        }
+    }
+}
+
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(BoostGamePlugin::headless());
+
+    app
+}
+
+// Finishes the plugins like `App::run` does, then updates until the
+// loading screen is done
+pub fn wait_for_assets(
+    app: &mut App,
+) -> Result<(), SimulationError> {
+    while app.plugins_state() == bevy::app::PluginsState::Adding
+    {
+        bevy::tasks::tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+
+    let started_at = Instant::now();
+
+    while *app.world().resource::<State<AppState>>().get()
+        == AppState::Setup
+    {
+        if started_at.elapsed() > LOADING_TIMEOUT {
+            return Err(SimulationError::LoadingTimedOut(
+                LOADING_TIMEOUT,
+            ));
+        }
+        app.update();
+    }
+
+    Ok(())
+}
+
+// Plays a level headless, the rocket is driven by an `InputScript`
+pub struct Simulation {
+    app: App,
+}
+
+impl Simulation {
+    pub fn new(level: Level, script: InputScript) -> Self {
+        let mut app = headless_app();
+
+        app.insert_resource(script)
+            .insert_resource(SimulationRun {
+                level,
+                tick: 0,
+                outcome: None,
+            })
+            .add_systems(OnEnter(AppState::MainMenu), start)
+            .add_systems(
+                FixedPreUpdate,
+                feed_script.run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                Update,
+                record_outcome.run_if(
+                    on_event::<PlayerCrashed>
+                        .or(on_event::<PlayerLanded>),
+                ),
+            );
+
+        Self { app }
+    }
+
+    // Where the replay of the run is written, next to this save file
+    pub fn with_save_path(mut self, save_path: PathBuf) -> Self {
+        self.app.insert_resource(SavePath(save_path));
+        self
+    }
+
+    pub fn run(
+        mut self,
+    ) -> Result<SimulationReport, SimulationError> {
+        wait_for_assets(&mut self.app)?;
+
+        let mut updates = 0;
+
+        loop {
+            let run =
+                self.app.world().resource::<SimulationRun>();
+            if run.outcome.is_some() {
+                break;
+            }
+            if run.tick >= MAX_TICKS {
+                self.app
+                    .world_mut()
+                    .resource_mut::<SimulationRun>()
+                    .outcome = Some(SimulationOutcome::TimedOut);
+                break;
+            }
+            if updates >= MAX_UPDATES {
+                return Err(SimulationError::RunTimedOut(
+                    MAX_UPDATES,
+                ));
+            }
+
+            self.app.update();
+            updates += 1;
+        }
+
+        let run = self.app.world().resource::<SimulationRun>();
+
+        Ok(SimulationReport {
+            level: run.level,
+            outcome: run
+                .outcome
+                .unwrap_or(SimulationOutcome::TimedOut),
+            ticks: run.tick,
+            elapsed_secs: run.tick as f32
+                / FIXED_TIMESTEP_HZ as f32,
+        })
+    }
+}
+
+fn start(
+    mut ew_change: EventWriter<ChangeEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+    run: Res<SimulationRun>,
+) {
+    ew_change.write(ChangeEvent::Set(run.level));
+    next_state.set(AppState::InGame);
+}
+
+// Presses the scripted actions right before the rocket reads them
+fn feed_script(
+    mut action_state: Single<
+        &mut ActionState<Action>,
+        With<Player>,
+    >,
+    mut run: ResMut<SimulationRun>,
+    script: Res<InputScript>,
+) {
+    let actions = script.actions_at(run.tick);
+
+    for action in Action::ALL {
+        let is_held = actions.contains(&action);
+
+        if action.is_axis() {
+            let value = if is_held { 1.0 } else { 0.0 };
+            action_state.set_value(&action, value);
+        } else if is_held {
+            action_state.press(&action);
+        } else {
+            action_state.release(&action);
+        }
+    }
+
+    run.tick += 1;
+}
+
+fn record_outcome(
+    mut er_crashed: EventReader<PlayerCrashed>,
+    mut er_landed: EventReader<PlayerLanded>,
+    mut run: ResMut<SimulationRun>,
+) {
+    if run.outcome.is_some() {
+        return;
+    }
+
+    if let Some(ev) = er_crashed.read().next() {
+        run.outcome = Some(SimulationOutcome::Failed(ev.reason));
+    } else if er_landed.read().next().is_some() {
+        run.outcome = Some(SimulationOutcome::Succeed);
+    }
+}
@@ -1,23 +1,33 @@
-use bevy::prelude::*;
+use bevy::{audio::Volume, prelude::*};
 
-use crate::{AppState, assets_loader::SceneAssets};
+use crate::{
+    AppState,
+    assets_loader::{
+        DEATH_EXPLOSION_SOUND, SUCCESS_SOUND, SceneAssets,
+    },
+    player::{PlayerCrashed, PlayerLanded},
+    save::Settings,
+};
 
 pub struct SoundsPlugin;
 
 impl Plugin for SoundsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            OnEnter(AppState::Failed),
-            play_death_explosion_sound,
+            Update,
+            (
+                play_death_explosion_sound
+                    .run_if(on_event::<PlayerCrashed>),
+                play_success_sound
+                    .run_if(on_event::<PlayerLanded>),
+                apply_volume
+                    .run_if(resource_changed::<Settings>),
+            ),
         )
         .add_systems(
             OnExit(AppState::Failed),
             clean_sound_effects,
         )
-        .add_systems(
-            OnEnter(AppState::Succeed),
-            play_success_sound,
-        )
         .add_systems(
             OnExit(AppState::Succeed),
             clean_sound_effects,
@@ -29,21 +39,31 @@ impl Plugin for SoundsPlugin {
 struct SoundEffectMarker;
 
 fn play_death_explosion_sound(
-    mut commands: Commands,
+    commands: Commands,
     sounds: Res<SceneAssets>,
 ) {
-    commands.spawn((
-        AudioPlayer::new(sounds.death_explosion_sound.clone()),
-        SoundEffectMarker,
-    ));
+    play_sound_effect(commands, &sounds, DEATH_EXPLOSION_SOUND);
 }
 
 fn play_success_sound(
-    mut commands: Commands,
+    commands: Commands,
     sounds: Res<SceneAssets>,
 ) {
+    play_sound_effect(commands, &sounds, SUCCESS_SOUND);
+}
+
+fn play_sound_effect(
+    mut commands: Commands,
+    sounds: &SceneAssets,
+    key: &str,
+) {
+    let Some(sound) = sounds.get_sound(key) else {
+        warn!("Unknown sound: {key}");
+        return;
+    };
+
     commands.spawn((
-        AudioPlayer::new(sounds.success_sound.clone()),
+        AudioPlayer::new(sound.clone()),
         SoundEffectMarker,
     ));
 }
@@ -63,3 +83,12 @@ fn clean_sound_effects(
         commands.entity(entity).despawn();
     }
 }
+
+fn apply_volume(
+    mut commands: Commands,
+    settings: Res<Settings>,
+) {
+    commands.insert_resource(GlobalVolume::new(Volume::Linear(
+        settings.master_volume,
+    )));
+}
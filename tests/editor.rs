@@ -0,0 +1,192 @@
+#![cfg(feature = "editor")]
+
+use bevy::prelude::*;
+
+use boost::{
+    AppState,
+    editor::{
+        EditorError, EditorObject, EditorTag, build_definition,
+        editor_objects,
+    },
+    level::{
+        ChangeEvent, LevelDefinition, LevelTransform, TweenMove,
+    },
+    player::Player,
+    simulation::{headless_app, wait_for_assets},
+};
+
+fn load_definition(path: &str) -> LevelDefinition {
+    let content =
+        std::fs::read_to_string(format!("assets/{path}"))
+            .expect("level file should exist");
+
+    ron::from_str(&content).expect("level file should parse")
+}
+
+// Stands in for the spawned editor entities, in spawn order
+fn placed_objects(
+    definition: &LevelDefinition,
+) -> Vec<(Entity, EditorObject, Transform)> {
+    editor_objects(definition)
+        .enumerate()
+        .map(|(index, (object, transform))| {
+            (
+                Entity::from_raw(index as u32 + 1),
+                object,
+                transform,
+            )
+        })
+        .collect()
+}
+
+fn rebuild(
+    template: &LevelDefinition,
+    objects: &[(Entity, EditorObject, Transform)],
+) -> Result<LevelDefinition, EditorError> {
+    build_definition(
+        template,
+        objects.iter().map(|(entity, object, transform)| {
+            (*entity, object, transform)
+        }),
+    )
+}
+
+fn assert_transform_eq(a: &LevelTransform, b: &LevelTransform) {
+    assert!(a.translation.abs_diff_eq(b.translation, 1e-4));
+    assert!(a.rotation.abs_diff_eq(b.rotation, 1e-3));
+    assert!(a.scale.abs_diff_eq(b.scale, 1e-4));
+}
+
+#[test]
+fn transform_round_trips_through_level_transform() {
+    let level_transform = LevelTransform {
+        translation: Vec3::new(1.5, -2.0, 0.25),
+        rotation: Vec3::new(10.0, -35.0, 120.0),
+        scale: Vec3::new(2.0, 1.0, 0.5),
+    };
+
+    let transform = Transform::from(&level_transform);
+
+    assert_transform_eq(
+        &LevelTransform::from(&transform),
+        &level_transform,
+    );
+}
+
+#[test]
+fn definition_round_trips_through_editor_objects() {
+    let mut definition =
+        load_definition("levels/fourth.level.ron");
+    definition.obstacles[0].transform = LevelTransform {
+        translation: Vec3::new(-1.0, 3.0, 0.0),
+        rotation: Vec3::new(0.0, 0.0, 45.0),
+        scale: Vec3::new(1.0, 2.0, 1.0),
+    };
+    definition.obstacles.push(definition.floor.clone());
+
+    let objects = placed_objects(&definition);
+    let rebuilt = rebuild(&definition, &objects).unwrap();
+
+    let pairs: Vec<_> =
+        rebuilt.objects().zip(definition.objects()).collect();
+    assert_eq!(pairs.len(), definition.objects().count());
+    for (rebuilt, original) in pairs {
+        assert_eq!(rebuilt.asset, original.asset);
+        assert_transform_eq(
+            &rebuilt.transform,
+            &original.transform,
+        );
+        assert_eq!(
+            rebuilt.tween_move.as_ref().map(tween_fields),
+            original.tween_move.as_ref().map(tween_fields),
+        );
+    }
+    assert_eq!(rebuilt.starting_fuel, definition.starting_fuel);
+    assert_eq!(
+        rebuilt.fuel_pickups.len(),
+        definition.fuel_pickups.len()
+    );
+}
+
+fn tween_fields(tween_move: &TweenMove) -> (Vec3, usize) {
+    (tween_move.target, tween_move.duration)
+}
+
+#[test]
+fn tags_are_checked() {
+    let definition = load_definition("levels/first.level.ron");
+    let mut objects = placed_objects(&definition);
+
+    objects[0].1.tag = EditorTag::Finish;
+    assert!(matches!(
+        rebuild(&definition, &objects),
+        Err(EditorError::DuplicateObject(EditorTag::Finish))
+    ));
+
+    objects[0].1.tag = EditorTag::Obstacle;
+    assert!(matches!(
+        rebuild(&definition, &objects),
+        Err(EditorError::MissingObject(EditorTag::Floor))
+    ));
+}
+
+// Where the player spawns when the rebuilt level is test-flown, like
+// the editor does on its test-fly key
+fn test_fly_spawn(definition: LevelDefinition) -> Vec3 {
+    let mut app = headless_app();
+    wait_for_assets(&mut app).expect("assets should load");
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<LevelDefinition>>()
+        .add(definition);
+    app.world_mut()
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::InGame);
+    app.world_mut().send_event(ChangeEvent::TestFly(handle));
+    app.update();
+    app.update();
+
+    app.world_mut()
+        .query_filtered::<&Transform, With<Player>>()
+        .single(app.world())
+        .expect("a player should be spawned")
+        .translation
+}
+
+fn tagged(
+    objects: &[(Entity, EditorObject, Transform)],
+    tag: EditorTag,
+) -> usize {
+    objects
+        .iter()
+        .position(|(_, object, _)| object.tag == tag)
+        .expect("every level has one of each pad")
+}
+
+#[test]
+fn test_flight_spawns_on_the_edited_launch_pad() {
+    let definition = load_definition("levels/fourth.level.ron");
+    let mut objects = placed_objects(&definition);
+    let default_spawn =
+        test_fly_spawn(rebuild(&definition, &objects).unwrap());
+
+    let offset = Vec3::new(4.0, 2.0, 0.0);
+    let launch_pad = tagged(&objects, EditorTag::LaunchPad);
+    objects[launch_pad].2.translation += offset;
+    let moved_spawn =
+        test_fly_spawn(rebuild(&definition, &objects).unwrap());
+
+    // Swapping the pads makes the moved one the landing pad
+    let finish = tagged(&objects, EditorTag::Finish);
+    objects[launch_pad].1.tag = EditorTag::Finish;
+    objects[finish].1.tag = EditorTag::LaunchPad;
+    let retagged_spawn =
+        test_fly_spawn(rebuild(&definition, &objects).unwrap());
+
+    // Gravity may already have pulled it down for a tick
+    assert!(
+        moved_spawn.abs_diff_eq(default_spawn + offset, 0.01)
+    );
+    assert!(retagged_spawn.abs_diff_eq(default_spawn, 0.01));
+}
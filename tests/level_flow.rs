@@ -0,0 +1,251 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use boost::{
+    AppState,
+    collision::CollisionEvent,
+    level::{
+        ChangeEvent, CurrentLevel, Finish, LEVELS_ORDER, Level,
+        LevelDefinition, LevelMarker, Obstacle, UnlockedLevels,
+    },
+    player::{
+        LandingRules, Player, PlayerCrashed, PlayerLanded,
+    },
+    simulation::{headless_app, wait_for_assets},
+};
+
+#[derive(Resource, Default)]
+struct Outcomes {
+    crashed: u32,
+    landed: u32,
+}
+
+fn count_outcomes(
+    mut er_crashed: EventReader<PlayerCrashed>,
+    mut er_landed: EventReader<PlayerLanded>,
+    mut outcomes: ResMut<Outcomes>,
+) {
+    outcomes.crashed += er_crashed.read().count() as u32;
+    outcomes.landed += er_landed.read().count() as u32;
+}
+
+fn loaded_app() -> App {
+    let mut app = headless_app();
+    app.init_resource::<Outcomes>()
+        .add_systems(Last, count_outcomes);
+
+    wait_for_assets(&mut app).expect("assets should load");
+    app
+}
+
+fn change_level(app: &mut App, change: ChangeEvent) {
+    app.world_mut().send_event(change);
+    app.update();
+    app.update();
+}
+
+fn current_index(app: &App) -> usize {
+    app.world().resource::<CurrentLevel>().index
+}
+
+// An unsaved copy of a level, like the editor test-flies
+fn test_flight_definition(
+    app: &mut App,
+    level: Level,
+) -> Handle<LevelDefinition> {
+    let content = std::fs::read_to_string(format!(
+        "assets/{}",
+        level.definition_path()
+    ))
+    .expect("level file should exist");
+    let definition: LevelDefinition = ron::from_str(&content)
+        .expect("level file should parse");
+
+    app.world_mut()
+        .resource_mut::<Assets<LevelDefinition>>()
+        .add(definition)
+}
+
+fn player_translation(app: &mut App) -> Vec3 {
+    app.world_mut()
+        .query_filtered::<&Transform, With<Player>>()
+        .single(app.world())
+        .expect("a player should be spawned")
+        .translation
+}
+
+fn is_unlocked(app: &App, level: Level) -> bool {
+    app.world().resource::<UnlockedLevels>().is_unlocked(level)
+}
+
+fn state(app: &App) -> AppState {
+    app.world().resource::<State<AppState>>().get().clone()
+}
+
+fn level_entities(app: &mut App) -> Vec<Entity> {
+    app.world_mut()
+        .query_filtered::<Entity, With<LevelMarker>>()
+        .iter(app.world())
+        .collect()
+}
+
+// Starts a new try of the current level with the physics frozen, so
+// only the collisions sent by the test happen
+fn start_try(app: &mut App) -> Entity {
+    start_try_with(app, ChangeEvent::Reload)
+}
+
+fn start_try_with(app: &mut App, change: ChangeEvent) -> Entity {
+    app.world_mut()
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::InGame);
+    change_level(app, change);
+    app.world_mut().resource_mut::<Time<Physics>>().pause();
+
+    app.world_mut()
+        .query_filtered::<Entity, With<Player>>()
+        .single(app.world())
+        .expect("a player should be spawned")
+}
+
+// Sends the collisions at once, so they are all read in the same update
+fn collide(app: &mut App, pairs: &[(Entity, Entity)]) {
+    assert_eq!(state(app), AppState::InGame);
+    for (a, b) in pairs {
+        app.world_mut().send_event(CollisionEvent(*a, *b));
+    }
+    app.update();
+    app.update();
+}
+
+#[test]
+fn next_wraps_around_levels_order() {
+    let mut app = loaded_app();
+    let last_level = LEVELS_ORDER[LEVELS_ORDER.len() - 1];
+
+    change_level(&mut app, ChangeEvent::Set(last_level));
+    assert_eq!(current_index(&app), LEVELS_ORDER.len() - 1);
+
+    change_level(&mut app, ChangeEvent::Next);
+    assert_eq!(current_index(&app), 0);
+}
+
+#[test]
+fn reload_keeps_level_index() {
+    let mut app = loaded_app();
+
+    change_level(&mut app, ChangeEvent::Set(Level::Second));
+    change_level(&mut app, ChangeEvent::Reload);
+
+    assert_eq!(current_index(&app), 1);
+}
+
+#[test]
+fn level_entities_are_despawned_between_loads() {
+    let mut app = loaded_app();
+
+    change_level(&mut app, ChangeEvent::Set(Level::First));
+    let first_load = level_entities(&mut app);
+    assert!(!first_load.is_empty());
+
+    change_level(&mut app, ChangeEvent::Reload);
+    let second_load = level_entities(&mut app);
+    assert_eq!(second_load.len(), first_load.len());
+
+    change_level(&mut app, ChangeEvent::Next);
+    for entity in first_load.iter().chain(second_load.iter()) {
+        assert!(app.world().get_entity(*entity).is_err());
+    }
+}
+
+#[test]
+fn obstacle_collision_fails_once_per_try() {
+    let mut app = loaded_app();
+    let obstacle = app.world_mut().spawn(Obstacle).id();
+
+    let player = start_try(&mut app);
+    collide(&mut app, &[(player, obstacle), (obstacle, player)]);
+
+    assert_eq!(state(&app), AppState::Failed);
+    assert_eq!(app.world().resource::<Outcomes>().crashed, 1);
+
+    let player = start_try(&mut app);
+    collide(&mut app, &[(player, obstacle)]);
+
+    assert_eq!(state(&app), AppState::Failed);
+    assert_eq!(app.world().resource::<Outcomes>().crashed, 2);
+}
+
+#[test]
+fn finish_collision_succeeds_once_per_try() {
+    let mut app = loaded_app();
+    let finish = app.world_mut().spawn(Finish).id();
+    app.world_mut().resource_mut::<LandingRules>().dwell_secs =
+        0.0;
+
+    let player = start_try(&mut app);
+    collide(&mut app, &[(player, finish), (finish, player)]);
+
+    assert_eq!(state(&app), AppState::Succeed);
+    assert_eq!(app.world().resource::<Outcomes>().landed, 1);
+    assert_eq!(app.world().resource::<Outcomes>().crashed, 0);
+
+    let player = start_try(&mut app);
+    collide(&mut app, &[(player, finish)]);
+
+    assert_eq!(state(&app), AppState::Succeed);
+    assert_eq!(app.world().resource::<Outcomes>().landed, 2);
+}
+
+#[test]
+fn test_flight_landing_unlocks_nothing() {
+    let mut app = loaded_app();
+    let finish = app.world_mut().spawn(Finish).id();
+    app.world_mut().resource_mut::<LandingRules>().dwell_secs =
+        0.0;
+    change_level(&mut app, ChangeEvent::Set(Level::First));
+
+    let test_flight =
+        test_flight_definition(&mut app, Level::First);
+    let player = start_try_with(
+        &mut app,
+        ChangeEvent::TestFly(test_flight),
+    );
+    collide(&mut app, &[(player, finish)]);
+
+    assert_eq!(state(&app), AppState::Succeed);
+    assert!(!is_unlocked(&app, Level::Second));
+
+    let player =
+        start_try_with(&mut app, ChangeEvent::Set(Level::First));
+    collide(&mut app, &[(player, finish)]);
+
+    assert_eq!(state(&app), AppState::Succeed);
+    assert!(is_unlocked(&app, Level::Second));
+}
+
+#[test]
+fn player_spawns_on_a_moved_launch_pad() {
+    let mut app = loaded_app();
+    change_level(&mut app, ChangeEvent::Set(Level::First));
+    start_try(&mut app);
+    let default_spawn = player_translation(&mut app);
+
+    let offset = Vec3::new(4.0, 2.0, 0.0);
+    let moved_pad =
+        test_flight_definition(&mut app, Level::First);
+    app.world_mut()
+        .resource_mut::<Assets<LevelDefinition>>()
+        .get_mut(&moved_pad)
+        .expect("definition was just added")
+        .launch_pad
+        .transform
+        .translation += offset;
+    start_try_with(&mut app, ChangeEvent::TestFly(moved_pad));
+
+    // Gravity may already have pulled it down for a tick
+    assert!(
+        player_translation(&mut app)
+            .abs_diff_eq(default_spawn + offset, 0.01)
+    );
+}
@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use boost::{
+    AppState,
+    controls::Action,
+    level::{ChangeEvent, Level},
+    player::{
+        Player, PlayerCrashed, PlayerLanded, ReplayPlayback,
+        WatchReplayEvent,
+    },
+    save::SavePath,
+    simulation::{headless_app, wait_for_assets},
+};
+
+// Gives up on a run that never ends instead of hanging the test
+const MAX_UPDATES: u32 = 64 * 30;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Outcome {
+    Crashed,
+    Landed,
+}
+
+#[derive(Resource, Default)]
+struct RunEnd(Option<(Outcome, Transform)>);
+
+#[derive(Resource, Default)]
+struct RecordedTicks(u32);
+
+// Thrusts while turning, so the run depends on every recorded tick
+fn feed_input(
+    mut action_state: Single<
+        &mut ActionState<Action>,
+        (With<Player>, Without<ReplayPlayback>),
+    >,
+    mut ticks: ResMut<RecordedTicks>,
+) {
+    action_state.set_value(&Action::Thrust, 1.0);
+    if ticks.0 < 40 {
+        action_state.press(&Action::RotateLeft);
+    } else {
+        action_state.release(&Action::RotateLeft);
+    }
+
+    ticks.0 += 1;
+}
+
+fn record_run_end(
+    mut er_crashed: EventReader<PlayerCrashed>,
+    mut er_landed: EventReader<PlayerLanded>,
+    player: Single<&Transform, With<Player>>,
+    mut run_end: ResMut<RunEnd>,
+) {
+    let outcome = if er_crashed.read().count() > 0 {
+        Outcome::Crashed
+    } else if er_landed.read().count() > 0 {
+        Outcome::Landed
+    } else {
+        return;
+    };
+
+    run_end.0.get_or_insert((outcome, **player));
+}
+
+fn loaded_app(save_dir: &str) -> App {
+    let mut app = headless_app();
+    app.insert_resource(SavePath(
+        std::env::temp_dir().join(save_dir).join("save.ron"),
+    ))
+    .init_resource::<RunEnd>()
+    .init_resource::<RecordedTicks>()
+    .add_systems(
+        FixedPreUpdate,
+        feed_input.run_if(in_state(AppState::InGame)),
+    )
+    .add_systems(Update, record_run_end);
+
+    wait_for_assets(&mut app).expect("assets should load");
+    app
+}
+
+fn run_until_end(app: &mut App) -> (Outcome, Transform) {
+    app.world_mut().resource_mut::<RunEnd>().0 = None;
+
+    for _ in 0..MAX_UPDATES {
+        app.update();
+
+        if let Some(run_end) = app.world().resource::<RunEnd>().0
+        {
+            return run_end;
+        }
+    }
+
+    panic!("the run should end in {MAX_UPDATES} updates");
+}
+
+#[test]
+fn replay_reproduces_the_recorded_run() {
+    let mut app = loaded_app("boost-test-replay");
+
+    app.world_mut().send_event(ChangeEvent::Set(Level::First));
+    app.world_mut()
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::InGame);
+    let recorded = run_until_end(&mut app);
+
+    app.world_mut().send_event(WatchReplayEvent);
+    let replayed = run_until_end(&mut app);
+
+    assert!(
+        app.world_mut()
+            .query_filtered::<(), (With<Player>, With<ReplayPlayback>)>()
+            .single(app.world())
+            .is_ok(),
+        "the second run should be a replay"
+    );
+    assert_eq!(replayed.0, recorded.0);
+    assert_eq!(replayed.1, recorded.1);
+}
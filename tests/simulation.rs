@@ -0,0 +1,33 @@
+use boost::{
+    controls::Action,
+    level::Level,
+    player::CrashReason,
+    simulation::{InputScript, Simulation, SimulationOutcome},
+};
+
+#[test]
+fn turning_into_the_ground_fails() {
+    let script = InputScript::default()
+        .hold(&[Action::Boost, Action::RotateLeft], 40)
+        .hold(&[Action::Boost], 200);
+
+    let report = Simulation::new(Level::First, script)
+        .run()
+        .expect("simulation should run");
+
+    assert_eq!(report.level, Level::First);
+    assert!(matches!(
+        report.outcome,
+        SimulationOutcome::Failed(CrashReason::Obstacle)
+    ));
+}
+
+#[test]
+fn idle_rocket_times_out_on_the_launch_pad() {
+    let report =
+        Simulation::new(Level::First, InputScript::default())
+            .run()
+            .expect("simulation should run");
+
+    assert_eq!(report.outcome, SimulationOutcome::TimedOut);
+}